@@ -153,5 +153,8 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		}),
+		pallet_poe: Some(Default::default()),
+		pallet_poe_Instance1: Some(Default::default()),
+		pallet_poe_Instance2: Some(Default::default()),
 	}
 }
@@ -0,0 +1,519 @@
+//! RPC interface for the PoE pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{offchain::OffchainStorage, Bytes, H256};
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, NumberFor, One, SaturatedConversion},
+};
+
+pub use pallet_poe_runtime_api::{
+    Claim, ClaimStats, ClaimsPage, HashAlgorithm, NamespaceId, PoeApi as PoeRuntimeApi, Registry,
+    Tombstone,
+};
+
+/// Queries against the proof registries, each selected by a `registry` parameter.
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber, Balance> {
+    /// The claim on `proof` in `namespace` at the given block, or the best block if none is given.
+    #[rpc(name = "poe_claim")]
+    fn claim(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Claim<AccountId, BlockNumber, Balance>>>;
+
+    /// The claim in `namespace` on the document whose `digest` was computed with `algorithm`.
+    #[rpc(name = "poe_claimOfDigest")]
+    fn claim_of_digest(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        algorithm: HashAlgorithm,
+        digest: H256,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Claim<AccountId, BlockNumber, Balance>>>;
+
+    /// All versions of the document `proof` is a version of, oldest first.
+    #[rpc(name = "poe_versionHistory")]
+    fn version_history(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<Bytes>>;
+
+    /// The proofs the claim on `proof` referenced when it was created.
+    #[rpc(name = "poe_references")]
+    fn references(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<Bytes>>;
+
+    /// The claims citing `proof`.
+    #[rpc(name = "poe_citedBy")]
+    fn cited_by(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<Bytes>>;
+
+    /// The document stored on-chain with the claim on `proof`.
+    #[rpc(name = "poe_document")]
+    fn document(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Bytes>>;
+
+    /// The content claimed on `proof` with `create_claim_with_content`, as indexed in the
+    /// offchain database of this node.
+    ///
+    /// Only available on nodes running with offchain indexing enabled.
+    #[rpc(name = "poe_offchainDocument")]
+    fn offchain_document(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Bytes>>;
+
+    /// The account approved to transfer the claim on `proof`.
+    #[rpc(name = "poe_approved")]
+    fn approved(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<AccountId>>;
+
+    /// Whether `operator` is approved to manage all claims of `owner`.
+    #[rpc(name = "poe_isApprovedForAll")]
+    fn is_approved_for_all(
+        &self,
+        registry: Registry,
+        owner: AccountId,
+        operator: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<bool>;
+
+    /// The block at the start of which the claim on `proof` is revoked, if its revocation has
+    /// been requested.
+    #[rpc(name = "poe_pendingRevocation")]
+    fn pending_revocation(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<BlockNumber>>;
+
+    /// The block at the start of which the claim on `proof` expires.
+    #[rpc(name = "poe_expiry")]
+    fn expiry(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<BlockNumber>>;
+
+    /// The last revoked claim on `proof`.
+    #[rpc(name = "poe_tombstone")]
+    fn tombstone(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Tombstone<AccountId, BlockNumber>>>;
+
+    /// The counters of `registry`.
+    #[rpc(name = "poe_stats")]
+    fn stats(&self, registry: Registry, at: Option<BlockHash>) -> Result<ClaimStats>;
+
+    /// The number of claims currently owned by `owner`.
+    #[rpc(name = "poe_ownerClaimCount")]
+    fn owner_claim_count(
+        &self,
+        registry: Registry,
+        owner: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<u32>;
+
+    /// The events of the blocks `from` to `to` indexed under `topic`, with the hash of their
+    /// block. Events are returned as SCALE-encoded `EventRecord`s.
+    ///
+    /// At most `MAX_EVENT_RANGE` blocks can be queried at once. Blocks whose state has been
+    /// pruned cannot be queried.
+    #[rpc(name = "poe_eventsByTopic")]
+    fn events_by_topic(
+        &self,
+        topic: H256,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<(BlockHash, Bytes)>>;
+
+    /// The claims made from block `from` to block `to`, starting at `cursor` if given and
+    /// returning at most `limit` claims, clamped between one and `MAX_PAGE_SIZE`.
+    ///
+    /// Pass the `next` cursor of a page to fetch the following one.
+    #[rpc(name = "poe_claimsInRange")]
+    fn claims_in_range(
+        &self,
+        registry: Registry,
+        from: BlockNumber,
+        to: BlockNumber,
+        cursor: Option<(BlockNumber, u32)>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> Result<ClaimsPage<BlockNumber, Bytes>>;
+}
+
+/// The maximum number of blocks `poe_eventsByTopic` searches.
+pub const MAX_EVENT_RANGE: u64 = 1_000;
+
+/// The maximum number of claims `poe_claimsInRange` returns at once.
+pub const MAX_PAGE_SIZE: u32 = 1_000;
+
+/// Error code returned when a call into the runtime fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when the node has no offchain storage.
+const OFFCHAIN_STORAGE_UNAVAILABLE: i64 = 2;
+/// Error code returned when a block range is empty or too long.
+const INVALID_RANGE: i64 = 3;
+
+fn runtime_error(err: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Runtime call failed.".into(),
+        data: Some(format!("{:?}", err).into()),
+    }
+}
+
+/// Implements the `PoeApi` RPC trait by calling into the runtime.
+pub struct Poe<C, Block, S> {
+    client: Arc<C>,
+    offchain_storage: Option<S>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block, S> Poe<C, Block, S> {
+    /// Create a new `Poe` with the given reference to the client and the offchain storage of
+    /// the node, if any.
+    pub fn new(client: Arc<C>, offchain_storage: Option<S>) -> Self {
+        Self {
+            client,
+            offchain_storage,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, S> Poe<C, Block, S>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        // Default to the best block if no hash is given.
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C, Block, S, AccountId, BlockNumber, Balance>
+    PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber, Balance> for Poe<C, Block, S>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber, Balance>,
+    S: OffchainStorage + 'static,
+    AccountId: Codec,
+    BlockNumber: Codec,
+    Balance: Codec,
+    NumberFor<Block>: From<BlockNumber>,
+{
+    fn claim(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Claim<AccountId, BlockNumber, Balance>>> {
+        self.client
+            .runtime_api()
+            .claim(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)
+    }
+
+    fn claim_of_digest(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        algorithm: HashAlgorithm,
+        digest: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Claim<AccountId, BlockNumber, Balance>>> {
+        self.client
+            .runtime_api()
+            .claim_of_digest(
+                &self.block_id(at),
+                registry,
+                namespace,
+                algorithm,
+                digest.into(),
+            )
+            .map_err(runtime_error)
+    }
+
+    fn version_history(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<Bytes>> {
+        let history = self
+            .client
+            .runtime_api()
+            .version_history(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)?;
+        Ok(history.into_iter().map(Bytes).collect())
+    }
+
+    fn references(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<Bytes>> {
+        let references = self
+            .client
+            .runtime_api()
+            .references(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)?;
+        Ok(references.into_iter().map(Bytes).collect())
+    }
+
+    fn cited_by(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<Bytes>> {
+        let citing = self
+            .client
+            .runtime_api()
+            .cited_by(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)?;
+        Ok(citing.into_iter().map(Bytes).collect())
+    }
+
+    fn document(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Bytes>> {
+        let document = self
+            .client
+            .runtime_api()
+            .document(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)?;
+        Ok(document.map(Bytes))
+    }
+
+    fn offchain_document(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Bytes>> {
+        let storage = self.offchain_storage.as_ref().ok_or_else(|| RpcError {
+            code: ErrorCode::ServerError(OFFCHAIN_STORAGE_UNAVAILABLE),
+            message: "Offchain storage is not available.".into(),
+            data: None,
+        })?;
+        let key = self
+            .client
+            .runtime_api()
+            .offchain_document_key(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)?;
+        Ok(key
+            .and_then(|key| storage.get(sp_offchain::STORAGE_PREFIX, &key))
+            .map(Bytes))
+    }
+
+    fn approved(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<AccountId>> {
+        self.client
+            .runtime_api()
+            .approved(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)
+    }
+
+    fn is_approved_for_all(
+        &self,
+        registry: Registry,
+        owner: AccountId,
+        operator: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<bool> {
+        self.client
+            .runtime_api()
+            .is_approved_for_all(&self.block_id(at), registry, owner, operator)
+            .map_err(runtime_error)
+    }
+
+    fn pending_revocation(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<BlockNumber>> {
+        self.client
+            .runtime_api()
+            .pending_revocation(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)
+    }
+
+    fn expiry(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<BlockNumber>> {
+        self.client
+            .runtime_api()
+            .expiry(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)
+    }
+
+    fn tombstone(
+        &self,
+        registry: Registry,
+        namespace: NamespaceId,
+        proof: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Tombstone<AccountId, BlockNumber>>> {
+        self.client
+            .runtime_api()
+            .tombstone(&self.block_id(at), registry, namespace, proof.to_vec())
+            .map_err(runtime_error)
+    }
+
+    fn stats(&self, registry: Registry, at: Option<<Block as BlockT>::Hash>) -> Result<ClaimStats> {
+        self.client
+            .runtime_api()
+            .stats(&self.block_id(at), registry)
+            .map_err(runtime_error)
+    }
+
+    fn owner_claim_count(
+        &self,
+        registry: Registry,
+        owner: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<u32> {
+        self.client
+            .runtime_api()
+            .owner_claim_count(&self.block_id(at), registry, owner)
+            .map_err(runtime_error)
+    }
+
+    fn events_by_topic(
+        &self,
+        topic: H256,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<(<Block as BlockT>::Hash, Bytes)>> {
+        let from = NumberFor::<Block>::from(from);
+        let to = NumberFor::<Block>::from(to);
+        if to < from || (to - from).saturated_into::<u64>() >= MAX_EVENT_RANGE {
+            return Err(RpcError {
+                code: ErrorCode::ServerError(INVALID_RANGE),
+                message: format!("Block ranges must span 1 to {} blocks.", MAX_EVENT_RANGE),
+                data: None,
+            });
+        }
+
+        let mut events = Vec::new();
+        let mut number = from;
+        while number <= to {
+            let hash = match self.client.hash(number).map_err(runtime_error)? {
+                Some(hash) => hash,
+                None => break,
+            };
+            let records = self
+                .client
+                .runtime_api()
+                .events_by_topic(&BlockId::hash(hash), topic.into())
+                .map_err(runtime_error)?;
+            events.extend(records.into_iter().map(|record| (hash, Bytes(record))));
+            number = number + One::one();
+        }
+        Ok(events)
+    }
+
+    fn claims_in_range(
+        &self,
+        registry: Registry,
+        from: BlockNumber,
+        to: BlockNumber,
+        cursor: Option<(BlockNumber, u32)>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<ClaimsPage<BlockNumber, Bytes>> {
+        let page = self
+            .client
+            .runtime_api()
+            .claims_in_range(
+                &self.block_id(at),
+                registry,
+                from,
+                to,
+                cursor,
+                limit.max(1).min(MAX_PAGE_SIZE),
+            )
+            .map_err(runtime_error)?;
+        Ok(ClaimsPage {
+            claims: page
+                .claims
+                .into_iter()
+                .map(|(block, namespace, proof)| (block, namespace, Bytes(proof)))
+                .collect(),
+            next: page.next,
+        })
+    }
+}
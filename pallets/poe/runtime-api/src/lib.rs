@@ -0,0 +1,127 @@
+//! Runtime API definition for the PoE pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
+
+pub use pallet_poe::{
+    Claim, ClaimMetadata, ClaimStats, ClaimsPage, HashAlgorithm, NamespaceId, Tombstone,
+};
+
+/// A proof registry of the runtime, each an instance of the PoE pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum Registry {
+    /// The general purpose registry, the default instance.
+    General,
+    /// The registry of signed contracts, `Instance1`.
+    Contract,
+    /// The registry of release digests, `Instance2`.
+    Release,
+}
+
+sp_api::decl_runtime_apis! {
+    /// Queries against the proof registries of the runtime.
+    #[api_version(2)]
+    pub trait PoeApi<AccountId, BlockNumber, Balance> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+        Balance: Codec,
+    {
+        /// The claim on `proof` in `namespace`, if any.
+        fn claim(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<Claim<AccountId, BlockNumber, Balance>>;
+
+        /// The claim in `namespace` on the document whose `digest` was computed with `algorithm`,
+        /// if any.
+        fn claim_of_digest(
+            registry: Registry,
+            namespace: NamespaceId,
+            algorithm: HashAlgorithm,
+            digest: [u8; 32],
+        ) -> Option<Claim<AccountId, BlockNumber, Balance>>;
+
+        /// All versions of the document `proof` is a version of, oldest first.
+        fn version_history(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Vec<Vec<u8>>;
+
+        /// The proofs the claim on `proof` referenced when it was created.
+        fn references(registry: Registry, namespace: NamespaceId, proof: Vec<u8>) -> Vec<Vec<u8>>;
+
+        /// The claims citing `proof`.
+        fn cited_by(registry: Registry, namespace: NamespaceId, proof: Vec<u8>) -> Vec<Vec<u8>>;
+
+        /// The document stored on-chain with the claim on `proof`, if any.
+        fn document(registry: Registry, namespace: NamespaceId, proof: Vec<u8>) -> Option<Vec<u8>>;
+
+        /// The key of the offchain database under which the content claimed on `proof` is
+        /// indexed, or `None` if `proof` has no form the registry stores proofs in.
+        fn offchain_document_key(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<Vec<u8>>;
+
+        /// The account approved to transfer the claim on `proof`, if any.
+        fn approved(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<AccountId>;
+
+        /// Whether `operator` is approved to manage all claims of `owner`.
+        fn is_approved_for_all(registry: Registry, owner: AccountId, operator: AccountId) -> bool;
+
+        /// The block at the start of which the claim on `proof` is revoked, if its revocation
+        /// has been requested.
+        fn pending_revocation(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<BlockNumber>;
+
+        /// The block at the start of which the claim on `proof` expires unless it is renewed.
+        fn expiry(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<BlockNumber>;
+
+        /// The last revoked claim on `proof`, if any.
+        fn tombstone(
+            registry: Registry,
+            namespace: NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<Tombstone<AccountId, BlockNumber>>;
+
+        /// The counters of `registry`.
+        fn stats(registry: Registry) -> ClaimStats;
+
+        /// The number of claims currently owned by `owner`.
+        fn owner_claim_count(registry: Registry, owner: AccountId) -> u32;
+
+        /// The SCALE-encoded `EventRecord`s of the block indexed under `topic`, such as
+        /// `Module::proof_topic` or `Module::account_topic`.
+        fn events_by_topic(topic: [u8; 32]) -> Vec<Vec<u8>>;
+
+        /// The claims made from block `from` to block `to`, starting at `cursor` if given and
+        /// returning at most `limit` claims.
+        fn claims_in_range(
+            registry: Registry,
+            from: BlockNumber,
+            to: BlockNumber,
+            cursor: Option<(BlockNumber, u32)>,
+            limit: u32,
+        ) -> ClaimsPage<BlockNumber, Vec<u8>>;
+    }
+}
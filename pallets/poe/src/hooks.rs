@@ -0,0 +1,24 @@
+//! Handlers letting other pallets react to changes of the registry.
+
+use impl_trait_for_tuples::impl_for_tuples;
+
+/// Handler for claims being created.
+#[impl_for_tuples(30)]
+pub trait OnClaimCreated<AccountId> {
+    /// Called once `owner` has claimed `proof`.
+    fn on_claim_created(owner: &AccountId, proof: &[u8]);
+}
+
+/// Handler for claims changing hands.
+#[impl_for_tuples(30)]
+pub trait OnClaimTransferred<AccountId> {
+    /// Called once the claim on `proof` has been handed over from `from` to `to`.
+    fn on_claim_transferred(from: &AccountId, to: &AccountId, proof: &[u8]);
+}
+
+/// Handler for claims being revoked.
+#[impl_for_tuples(30)]
+pub trait OnClaimRevoked<AccountId> {
+    /// Called once the claim of `owner` on `proof` has been revoked.
+    fn on_claim_revoked(owner: &AccountId, proof: &[u8]);
+}
@@ -3,11 +3,40 @@
 /// Edit this file to define custom logic or remove it if it is not needed.
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// https://substrate.dev/docs/en/knowledgebase/runtime/frame
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, traits::Get, StorageMap,
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    traits::{
+        Contains, Currency, EnsureOrigin, ExistenceRequirement, Get, OnUnbalanced,
+        ReservableCurrency, WithdrawReason, WithdrawReasons,
+    },
+    transactional,
+    weights::Weight,
+    IterableStorageDoubleMap, Parameter, StorageDoubleMap, StorageMap, StorageValue,
 };
-use frame_system::ensure_signed;
-use sp_std::vec::Vec;
+use frame_system::{
+    ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction},
+};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{
+        AccountIdConversion, CheckedAdd, Hash, MaybeSerializeDeserialize, Member, One, Saturating,
+        Zero,
+    },
+    ModuleId, RuntimeDebug,
+};
+use sp_std::{convert::TryInto, vec, vec::Vec};
+
+pub mod hooks;
+pub mod migration;
+pub mod offchain;
+pub mod validation;
+
+pub use hooks::{OnClaimCreated, OnClaimRevoked, OnClaimTransferred};
+pub use validation::ProofValidator;
 
 #[cfg(test)]
 mod mock;
@@ -15,10 +44,329 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub type BalanceOf<T, I> =
+    <<T as Trait<I>>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub type NegativeImbalanceOf<T, I> = <<T as Trait<I>>::Currency as Currency<
+    <T as frame_system::Trait>::AccountId,
+>>::NegativeImbalance;
+
+pub type ClaimOf<T, I> = Claim<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+    BalanceOf<T, I>,
+>;
+
+/// A proof registered by an account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Claim<AccountId, BlockNumber, Balance> {
+    /// The account owning the claim.
+    pub owner: AccountId,
+    /// The block in which the claim was created.
+    pub block_number: BlockNumber,
+    /// The amount reserved from the owner while the claim exists.
+    pub deposit: Balance,
+    /// What the proof is for, as described by the owner.
+    pub metadata: ClaimMetadata,
+    /// The amount reserved from the owner for storing `metadata`.
+    pub metadata_deposit: Balance,
+    /// The hash function the proof was derived with, if known.
+    pub algorithm: Option<HashAlgorithm>,
+}
+
+/// A hash function documents are digested with.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum HashAlgorithm {
+    /// BLAKE2b with a 256 bit digest.
+    Blake2_256,
+    /// SHA-256.
+    Sha2_256,
+    /// Keccak-256, as used by Ethereum.
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    /// Every supported hash function.
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Blake2_256,
+        HashAlgorithm::Sha2_256,
+        HashAlgorithm::Keccak256,
+    ];
+
+    /// The digest of `data`.
+    pub fn hash(self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Blake2_256 => sp_io::hashing::blake2_256(data),
+            HashAlgorithm::Sha2_256 => sp_io::hashing::sha2_256(data),
+            HashAlgorithm::Keccak256 => sp_io::hashing::keccak_256(data),
+        }
+    }
+
+    /// The varint code of the hash function in the
+    /// [multihash table](https://github.com/multiformats/multicodec/blob/master/table.csv).
+    pub fn multihash_code(self) -> &'static [u8] {
+        match self {
+            HashAlgorithm::Blake2_256 => &[0xa0, 0xe4, 0x02],
+            HashAlgorithm::Sha2_256 => &[0x12],
+            HashAlgorithm::Keccak256 => &[0x1b],
+        }
+    }
+}
+
+/// Optional description of the document behind a proof.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ClaimMetadata {
+    /// The MIME type of the document, e.g. `application/pdf`.
+    pub content_type: Option<Vec<u8>>,
+    /// The size of the document, in bytes.
+    pub size: Option<u64>,
+    /// Where the document can be retrieved from.
+    pub uri: Option<Vec<u8>>,
+    /// A free-form note.
+    pub note: Option<Vec<u8>>,
+}
+
+/// What remains of a revoked claim.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Tombstone<AccountId, BlockNumber> {
+    /// The account that owned the claim.
+    pub owner: AccountId,
+    /// The block in which the claim was created.
+    pub claimed_at: BlockNumber,
+    /// The block in which the claim was revoked.
+    pub revoked_at: BlockNumber,
+    /// Why the claim was revoked, as given by the owner.
+    pub reason: Option<Vec<u8>>,
+}
+
+/// Who may claim a proof again after its claim has been revoked.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReclaimPolicy {
+    /// Anyone may claim the proof again.
+    Allow,
+    /// Nobody may claim the proof again.
+    Deny,
+    /// Only the owner of the revoked claim may claim the proof again.
+    OwnerOnly,
+}
+
+/// Counters describing the growth of a registry.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ClaimStats {
+    /// The number of claims currently registered.
+    pub count: u64,
+    /// The number of claims ever created.
+    pub created: u64,
+    /// The number of claims ever revoked.
+    pub revoked: u64,
+    /// The number of claims ever removed at the end of their period.
+    pub expired: u64,
+}
+
+/// A page of the claims made within a range of blocks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ClaimsPage<BlockNumber, Proof> {
+    /// The block, namespace and proof of each claim, in the order the claims were made.
+    pub claims: Vec<(BlockNumber, NamespaceId, Proof)>,
+    /// The cursor to fetch the next page with, if the range has not been fully searched.
+    pub next: Option<(BlockNumber, u32)>,
+}
+
+/// The maximum number of blocks `Module::claims_in_range` searches for a single page.
+pub const MAX_BLOCKS_PER_PAGE: u32 = 10_000;
+
+/// An offer to hand a claim over to another account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PendingTransfer<AccountId, BlockNumber> {
+    /// The account the claim is offered to.
+    pub to: AccountId,
+    /// The last block in which the offer can be accepted.
+    pub expires: BlockNumber,
+}
+
+/// The version of the storage layout of the pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// Proofs are stored as `Vec<u8>`.
+    V1,
+    /// Proofs are stored as `Trait::Proof`.
+    V2,
+    /// Claims record the hash function of their proof.
+    V3,
+    /// Proofs are claimed within namespaces.
+    V4,
+    /// Claims are indexed by the block they were made in.
+    V5,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
+}
+
+/// A type proofs can be stored as.
+pub trait ProofType: Parameter + Member + MaybeSerializeDeserialize + AsRef<[u8]> {
+    /// The proof made of `bytes`, if they have the right form.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// The proof of a document whose `digest` was computed with `algorithm`.
+    fn from_digest(_algorithm: HashAlgorithm, digest: &[u8; 32]) -> Option<Self> {
+        Self::from_bytes(digest)
+    }
+
+    /// The hash function the proof names, if its form tells.
+    fn algorithm(&self) -> Option<HashAlgorithm> {
+        None
+    }
+}
+
+/// Proofs of any length, such as multihashes.
+impl ProofType for Vec<u8> {
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+
+    /// The multihash of the document, so that the proof names its hash function.
+    fn from_digest(algorithm: HashAlgorithm, digest: &[u8; 32]) -> Option<Self> {
+        Some(
+            [
+                algorithm.multihash_code(),
+                &[digest.len() as u8],
+                &digest[..],
+            ]
+            .concat(),
+        )
+    }
+
+    /// The hash function of the multihash, if the proof is the multihash of a 32 byte digest.
+    fn algorithm(&self) -> Option<HashAlgorithm> {
+        HashAlgorithm::ALL.iter().copied().find(|algorithm| {
+            let code = algorithm.multihash_code();
+            self.len() == code.len() + 1 + 32 && self.starts_with(code) && self[code.len()] == 32
+        })
+    }
+}
+
+/// Raw 32 byte digests.
+impl ProofType for H256 {
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() == H256::len_bytes() {
+            Some(H256::from_slice(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+/// Identifier of an organization.
+pub type OrgId = u32;
+
+/// Identifier of a namespace, within which proofs are claimed.
+pub type NamespaceId = u32;
+
+/// The namespace open to every account, holding the claims made before namespaces existed.
+pub const PUBLIC_NAMESPACE: NamespaceId = 0;
+
+/// The weight charged for each byte of content a call hashes and copies, a conservative
+/// estimate for the slowest `HashAlgorithm`.
+pub const WEIGHT_PER_CONTENT_BYTE: Weight = 5_000;
+
+/// The prefix of the keys under which `create_claim_with_content` indexes documents in the
+/// offchain database of the node.
+pub const OFFCHAIN_DOCUMENT_PREFIX: &[u8] = b"poe::document";
+
+/// The role of a member within an organization.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum OrgRole {
+    /// May manage the members and the claims of the organization.
+    Admin,
+    /// May create claims on behalf of the organization.
+    Claimer,
+}
+
 /// Configure the pallet by specifying the parameters and types on which it depends.
-pub trait Trait: frame_system::Trait {
+pub trait Trait<I: Instance = DefaultInstance>:
+    frame_system::Trait + CreateSignedTransaction<Call<Self, I>>
+{
     /// Because this pallet emits events, it depends on the runtime's definition of an event.
-    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+    /// The currency in which claim deposits are reserved.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The type proofs are stored as.
+    type Proof: ProofType;
+    /// The hash function `store_document` derives proofs with.
+    type ProofHashing: Get<HashAlgorithm>;
+    /// The maximum length of a document hashed by `create_claim_from_content`, in bytes.
+    type MaxContentLength: Get<u32>;
+    /// The amount reserved from the owner of a claim until it is revoked.
+    type ClaimDeposit: Get<BalanceOf<Self, I>>;
+    /// The minimum length of a proof, in bytes.
+    type MinProofLength: Get<u32>;
+    /// The maximum length of a proof, in bytes.
+    type MaxProofLength: Get<u32>;
+    /// Checks the format of a proof before it can be claimed.
+    type ProofValidator: ProofValidator;
+    /// The maximum length of each text field of `ClaimMetadata`, in bytes.
+    type MaxMetadataLength: Get<u32>;
+    /// The amount reserved per byte of encoded `ClaimMetadata` attached to a claim.
+    type MetadataDepositPerByte: Get<BalanceOf<Self, I>>;
+    /// The maximum number of proofs a claim can reference.
+    type MaxReferences: Get<u32>;
+    /// The maximum number of claims made in a single block.
+    type MaxClaimsPerBlock: Get<u32>;
+    /// The maximum length of a document stored on-chain by `store_document`, in bytes.
+    type MaxInlineDocument: Get<u32>;
+    /// The amount reserved per byte of a document stored on-chain.
+    type DocumentDepositPerByte: Get<BalanceOf<Self, I>>;
+    /// The maximum length of a document indexed off-chain by `create_claim_with_content`, in
+    /// bytes.
+    type MaxIndexedDocument: Get<u32>;
+    /// The amount reserved from the owner of a namespace.
+    type NamespaceDeposit: Get<BalanceOf<Self, I>>;
+    /// Identifier from which the accounts of organizations are derived.
+    type ModuleId: Get<ModuleId>;
+    /// The number of blocks during which an offered claim can be accepted.
+    type OfferExpiry: Get<Self::BlockNumber>;
+    /// The number of blocks between a request to revoke a claim and its removal, during which
+    /// the request can be cancelled. If zero, claims are revoked at once.
+    type RevokeDelay: Get<Self::BlockNumber>;
+    /// Who may claim a proof again after its claim has been revoked.
+    type ReclaimPolicy: Get<ReclaimPolicy>;
+    /// The number of blocks after which a claim expires unless it is renewed. If zero, claims
+    /// do not expire.
+    type ClaimPeriod: Get<Self::BlockNumber>;
+    /// The fee paid per block a claim is renewed for.
+    type RenewalFeePerBlock: Get<BalanceOf<Self, I>>;
+    /// Handler for the renewal fees, e.g. a treasury. `()` burns them.
+    type OnRenewalFee: OnUnbalanced<NegativeImbalanceOf<Self, I>>;
+    /// Handler called when a claim is created.
+    type OnClaimCreated: OnClaimCreated<Self::AccountId>;
+    /// Handler called when a claim changes hands.
+    type OnClaimTransferred: OnClaimTransferred<Self::AccountId>;
+    /// Handler called when a claim is revoked.
+    type OnClaimRevoked: OnClaimRevoked<Self::AccountId>;
+    /// The origin allowed to pause and unpause the registry.
+    type PauseOrigin: EnsureOrigin<Self::Origin>;
+    /// The origin allowed to report verifications of claimed documents, resolving to the
+    /// account the verification is recorded for. `EnsureSignedBy<Module<T, I>, _>` admits the
+    /// accounts set with `set_verifiers`.
+    type VerifierOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+    /// The origin allowed to set the verifiers of the registry.
+    type VerifierManagerOrigin: EnsureOrigin<Self::Origin>;
+    /// The maximum number of verifiers whose reports are kept for a claim, and so the maximum
+    /// number of verifiers that can be set.
+    type MaxVerifications: Get<u32>;
+    /// The keys the offchain worker signs its reports with.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 }
 
 // The pallet's runtime storage items.
@@ -26,32 +374,251 @@ pub trait Trait: frame_system::Trait {
 decl_storage! {
     // A unique name is used to ensure that the pallet's storage items are isolated.
     // This name may be updated, but each pallet in the runtime must use a unique name.
-    // ---------------------------------vvvvvvvvvvvvvv
-    trait Store for Module<T: Trait> as PoeModule {
-        Proofs: map hasher(blake2_128_concat) Vec<u8> => (T::AccountId, T::BlockNumber);
+    // Instances other than the default one additionally prefix it with the instance name.
+    // ---------------------------------------------------------------------vvvvvvvvvvvvvv
+    trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as PoeModule {
+        /// The claims of each namespace.
+        Proofs get(fn proofs): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<ClaimOf<T, I>>;
+        /// The claims made before `Releases::V2` on proofs that have no `Trait::Proof` form,
+        /// kept as evidence by the migration. They belong to `PUBLIC_NAMESPACE`.
+        LegacyProofs get(fn legacy_proof): map hasher(blake2_128_concat) Vec<u8> => Option<ClaimOf<T, I>>;
+        /// The previous version of an amended proof.
+        Supersedes get(fn supersedes): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<T::Proof>;
+        /// The next version of a proof that has been amended.
+        SupersededBy get(fn superseded_by): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<T::Proof>;
+        /// The proofs a claim referenced when it was created, within the same namespace.
+        References get(fn references): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Vec<T::Proof>;
+        /// Reverse index of `References`: the claims citing a proof.
+        CitedBy: double_map hasher(blake2_128_concat) (NamespaceId, T::Proof), hasher(blake2_128_concat) T::Proof => ();
+        /// Claims offered to another account, awaiting acceptance.
+        PendingTransfers get(fn pending_transfers): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<PendingTransfer<T::AccountId, T::BlockNumber>>;
+        /// The price of claims listed for sale.
+        Prices get(fn price): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<BalanceOf<T, I>>;
+        /// The account approved to transfer a claim on behalf of its owner.
+        Approvals get(fn approved): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<T::AccountId>;
+        /// Operators approved to manage all claims of an owner.
+        OperatorApprovals get(fn is_approved_for_all): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => bool;
+        /// The block at the start of which a claim pending revocation is removed.
+        PendingRevocations get(fn pending_revocation): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<T::BlockNumber>;
+        /// The reason given for each pending revocation, recorded in the tombstone of the claim.
+        RevocationReasons get(fn revocation_reason): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<Vec<u8>>;
+        /// The proofs scheduled for removal at the start of each block.
+        RevocationQueue: map hasher(twox_64_concat) T::BlockNumber => Vec<(NamespaceId, T::Proof)>;
+        /// The block at the start of which a claim expires, for registries with a `ClaimPeriod`.
+        Expiries get(fn expiry): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<T::BlockNumber>;
+        /// The proofs whose claims expire at the start of each block.
+        ExpiryQueue: map hasher(twox_64_concat) T::BlockNumber => Vec<(NamespaceId, T::Proof)>;
+        /// The number of claims currently registered.
+        ClaimCount get(fn claim_count): u64;
+        /// The number of claims ever created.
+        ClaimsCreatedTotal get(fn claims_created_total): u64;
+        /// The number of claims ever revoked.
+        ClaimsRevokedTotal get(fn claims_revoked_total): u64;
+        /// The number of claims ever removed at the end of their period.
+        ClaimsExpiredTotal get(fn claims_expired_total): u64;
+        /// The claims made in each block, in order, as long as they are not revoked.
+        ClaimsByBlock get(fn claims_by_block): map hasher(twox_64_concat) T::BlockNumber => Vec<(NamespaceId, T::Proof)>;
+        /// The number of claims currently owned by each account.
+        OwnerClaimCount get(fn owner_claim_count): map hasher(blake2_128_concat) T::AccountId => u32;
+
+        /// The version of the storage layout, used to migrate storage on runtime upgrades.
+        StorageVersion build(|_| Releases::V5): Releases;
+
+        /// Whether the registry is paused, rejecting all calls but `set_paused`.
+        Paused get(fn paused): bool;
+        /// The last revoked claim on each proof.
+        Tombstones get(fn tombstone): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<Tombstone<T::AccountId, T::BlockNumber>>;
+        /// The documents stored on-chain with their claims, and the amount reserved for them.
+        Documents: double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Option<(Vec<u8>, BalanceOf<T, I>)>;
+        /// The claims with a URI created in the current block, for the offchain worker to
+        /// verify.
+        UnverifiedClaims get(fn unverified_claims): Vec<(NamespaceId, T::Proof)>;
+        /// The accounts allowed to report verifications, in order.
+        Verifiers get(fn verifiers) config() build(|config: &GenesisConfig<T, I>| {
+            let mut verifiers = config.verifiers.clone();
+            verifiers.sort();
+            verifiers.dedup();
+            verifiers
+        }): Vec<T::AccountId>;
+        /// Whether the document behind each claim matched its proof, as reported by each of up
+        /// to `MaxVerifications` verifiers.
+        Verifications get(fn verifications): double_map hasher(twox_64_concat) NamespaceId, hasher(blake2_128_concat) T::Proof => Vec<(T::AccountId, bool)>;
+
+        /// The identifier of the next namespace to be registered.
+        NextNamespaceId get(fn next_namespace_id): NamespaceId = 1;
+        /// The owner of each registered namespace and the amount reserved from it.
+        Namespaces get(fn namespace): map hasher(twox_64_concat) NamespaceId => Option<(T::AccountId, BalanceOf<T, I>)>;
+        /// The offer to hand each namespace over to another account, if any.
+        PendingNamespaceTransfers get(fn pending_namespace_transfer): map hasher(twox_64_concat) NamespaceId => Option<PendingTransfer<T::AccountId, T::BlockNumber>>;
+
+        /// The identifier of the next organization to be created.
+        NextOrgId get(fn next_org_id): OrgId;
+        /// The number of admins of each organization.
+        OrgAdmins get(fn org_admins): map hasher(twox_64_concat) OrgId => Option<u32>;
+        /// The role of each member of an organization.
+        OrgMembers get(fn org_member): double_map hasher(twox_64_concat) OrgId, hasher(blake2_128_concat) T::AccountId => Option<OrgRole>;
+        /// The organization owning an account, for accounts derived by `org_account_id`.
+        OrgOfAccount get(fn org_of_account): map hasher(blake2_128_concat) T::AccountId => Option<OrgId>;
     }
 }
 
 // Pallets use events to inform users when important changes are made.
 // https://substrate.dev/docs/en/knowledgebase/runtime/events
 decl_event!(
-    pub enum Event<T>
+    pub enum Event<T, I = DefaultInstance>
     where
         AccountId = <T as frame_system::Trait>::AccountId,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+        Balance = BalanceOf<T, I>,
+        Proof = <T as Trait<I>>::Proof,
     {
-        /// Event emitted when a proof has been claimed
-        ClaimCreated(AccountId, Vec<u8>),
-        /// Event emitted when a clain is revoked by the owner
-        ClaimRevoked(AccountId, Vec<u8>),
+        /// Event emitted when a proof has been claimed. [owner, namespace, proof]
+        ClaimCreated(AccountId, NamespaceId, Proof),
+        /// Event emitted when a clain is revoked by the owner, or an admin of the organization
+        /// owning it. [owner, namespace, proof]
+        ClaimRevoked(AccountId, NamespaceId, Proof),
+        /// Event emitted when the owner requests the revocation of a claim.
+        /// [owner, namespace, proof, removed at]
+        ClaimPendingRevocation(AccountId, NamespaceId, Proof, BlockNumber),
+        /// Event emitted when a pending revocation is cancelled. [owner, namespace, proof]
+        RevocationCancelled(AccountId, NamespaceId, Proof),
+        /// Event emitted when a claim reaches the end of its period. [owner, namespace, proof]
+        ClaimExpired(AccountId, NamespaceId, Proof),
+        /// Event emitted when a claim is renewed. [owner, namespace, proof, expires at, fee]
+        ClaimRenewed(AccountId, NamespaceId, Proof, BlockNumber, Balance),
+        /// Event emitted when the owner replaces the metadata of a claim. [owner, namespace, proof]
+        ClaimMetadataSet(AccountId, NamespaceId, Proof),
+        /// Event emitted when a claim is superseded by a new version. [owner, namespace, old, new]
+        ClaimAmended(AccountId, NamespaceId, Proof, Proof),
+        /// Event emitted when a claim is offered to another account. [owner, to, namespace, proof]
+        ClaimOffered(AccountId, AccountId, NamespaceId, Proof),
+        /// Event emitted when an offer is withdrawn or declined. [namespace, proof]
+        OfferCancelled(NamespaceId, Proof),
+        /// Event emitted when a claim changes hands. [from, to, namespace, proof]
+        ClaimTransferred(AccountId, AccountId, NamespaceId, Proof),
+        /// Event emitted when an account is approved to transfer a claim, or the approval is
+        /// cleared if no account is given. [owner, namespace, proof, operator]
+        Approval(AccountId, NamespaceId, Proof, Option<AccountId>),
+        /// Event emitted when an operator is approved or disapproved to manage all claims of an
+        /// owner. [owner, operator, approved]
+        ApprovalForAll(AccountId, AccountId, bool),
+        /// Event emitted when a claim is listed for sale, or withdrawn from sale if no price is
+        /// given. [owner, namespace, proof, price]
+        ClaimListed(AccountId, NamespaceId, Proof, Option<Balance>),
+        /// Event emitted when a listed claim is bought. [seller, buyer, namespace, proof, price]
+        ClaimSold(AccountId, AccountId, NamespaceId, Proof, Balance),
+        /// Event emitted when the registry is paused or unpaused. [paused]
+        PausedSet(bool),
+        /// Event emitted when an organization is created. [org, account, creator]
+        OrgCreated(OrgId, AccountId, AccountId),
+        /// Event emitted when a member joins an organization or changes role. [org, member, role]
+        OrgMemberAdded(OrgId, AccountId, OrgRole),
+        /// Event emitted when a member leaves an organization. [org, member]
+        OrgMemberRemoved(OrgId, AccountId),
+        /// Event emitted when a namespace is registered. [namespace, owner]
+        NamespaceRegistered(NamespaceId, AccountId),
+        /// Event emitted when a namespace is offered to another account. [namespace, from, to]
+        NamespaceOffered(NamespaceId, AccountId, AccountId),
+        /// Event emitted when a namespace changes hands. [namespace, from, to]
+        NamespaceTransferred(NamespaceId, AccountId, AccountId),
+        /// Event emitted when a namespace is deregistered. [namespace, owner]
+        NamespaceDeregistered(NamespaceId, AccountId),
+        /// Event emitted when a document is stored with its claim. [owner, namespace, proof]
+        DocumentStored(AccountId, NamespaceId, Proof),
+        /// Event emitted when a verifier reports whether the document behind a claim matches
+        /// its proof. [verifier, namespace, proof, ok]
+        ClaimVerified(AccountId, NamespaceId, Proof, bool),
+        /// Event emitted when the verifiers of the registry are replaced. [verifiers]
+        VerifiersSet(Vec<AccountId>),
     }
 );
 
 // Errors inform users that something went wrong.
 decl_error! {
-    pub enum Error for Module<T: Trait> {
+    pub enum Error for Module<T: Trait<I>, I: Instance> {
+        /// The registry is paused.
+        PalletPaused,
+        /// The proof has already been claimed.
         ProofAlreadyClaimed,
+        /// The proof has not been claimed.
         NoSuchProof,
+        /// The proof is claimed by another account.
         NotProofOwner,
+        /// The proof is shorter than `MinProofLength`.
+        ProofTooShort,
+        /// The proof is longer than `MaxProofLength`.
+        ProofTooLong,
+        /// The proof is rejected by the `ProofValidator`.
+        InvalidProofFormat,
+        /// The document is longer than `MaxContentLength`.
+        ContentTooLong,
+        /// The document is longer than `MaxInlineDocument` or `MaxIndexedDocument`.
+        DocumentTooLong,
+        /// The proof has been claimed and revoked before, and `ReclaimPolicy` forbids claiming
+        /// it again.
+        ProofTombstoned,
+        /// A text field of the metadata is longer than `MaxMetadataLength`.
+        MetadataTooLong,
+        /// The reason for a revocation is longer than `MaxMetadataLength`.
+        ReasonTooLong,
+        /// The claim has been superseded by a newer version.
+        ClaimSuperseded,
+        /// More references than `MaxReferences` were given.
+        TooManyReferences,
+        /// `MaxClaimsPerBlock` claims have already been made in this block.
+        TooManyClaimsInBlock,
+        /// A referenced proof has not been claimed.
+        NoSuchReference,
+        /// The same proof is referenced more than once.
+        DuplicateReference,
+        /// The claim or namespace has not been offered to anyone.
+        NoSuchOffer,
+        /// The claim or namespace has been offered to another account.
+        NotOfferRecipient,
+        /// The offer is older than `OfferExpiry`.
+        OfferExpired,
+        /// Claims must be revoked through `request_revoke`, as `RevokeDelay` is not zero.
+        RevocationDelayed,
+        /// The revocation of the claim has already been requested.
+        RevocationPending,
+        /// The revocation of the claim has not been requested.
+        NoPendingRevocation,
+        /// The claim has no expiry to extend.
+        ClaimDoesNotExpire,
+        /// The claim has reached the end of its period.
+        ClaimExpired,
+        /// A claim must be renewed by at least one block.
+        ZeroRenewal,
+        /// The renewal would extend the claim beyond the last representable block.
+        RenewalTooLong,
+        /// The claim is not listed for sale.
+        NotForSale,
+        /// The price of the claim is higher than the buyer is willing to pay.
+        PriceTooHigh,
+        /// The buyer already owns the claim.
+        AlreadyOwner,
+        /// The sender is neither the owner of the claim nor approved to transfer it.
+        NotApproved,
+        /// The organization does not exist.
+        NoSuchOrg,
+        /// Every organization identifier has been used.
+        OrgIdOverflow,
+        /// The account is not a member of the organization.
+        NotOrgMember,
+        /// The member lacks the role required for the operation.
+        InsufficientOrgRole,
+        /// The last admin of an organization cannot be removed or demoted.
+        LastOrgAdmin,
+        /// The namespace has not been registered.
+        NoSuchNamespace,
+        /// Every namespace identifier has been used.
+        NamespaceIdOverflow,
+        /// The namespace is owned by another account.
+        NotNamespaceOwner,
+        /// `MaxVerifications` verifiers have already reported on the claim.
+        TooManyVerifications,
+        /// More than `MaxVerifications` verifiers were given.
+        TooManyVerifiers,
     }
 }
 
@@ -59,36 +626,1262 @@ decl_error! {
 // These functions materialize as "extrinsics", which are often compared to transactions.
 // Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 decl_module! {
-    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+    pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
         // Errors must be initialized if they are used by the pallet.
-        type Error = Error<T>;
+        type Error = Error<T, I>;
+
+        /// The amount reserved from the owner of a claim until it is revoked.
+        const ClaimDeposit: BalanceOf<T, I> = T::ClaimDeposit::get();
 
-        // Events must be initialized if they are used by the pallet.
-        fn deposit_event() = default;
+        /// The minimum length of a proof, in bytes.
+        const MinProofLength: u32 = T::MinProofLength::get();
 
-        /// Allow a user to claim ownership of an unclaimed proof.
+        /// The maximum length of a proof, in bytes.
+        const MaxProofLength: u32 = T::MaxProofLength::get();
+
+        /// The maximum length of a document hashed on-chain, in bytes.
+        const MaxContentLength: u32 = T::MaxContentLength::get();
+
+        /// The maximum length of each text field of the metadata, in bytes.
+        const MaxMetadataLength: u32 = T::MaxMetadataLength::get();
+
+        /// The amount reserved per byte of metadata attached to a claim.
+        const MetadataDepositPerByte: BalanceOf<T, I> = T::MetadataDepositPerByte::get();
+
+        /// The maximum length of a document stored on-chain, in bytes.
+        const MaxInlineDocument: u32 = T::MaxInlineDocument::get();
+
+        /// The amount reserved per byte of a document stored on-chain.
+        const DocumentDepositPerByte: BalanceOf<T, I> = T::DocumentDepositPerByte::get();
+
+        /// The maximum length of a document indexed off-chain, in bytes.
+        const MaxIndexedDocument: u32 = T::MaxIndexedDocument::get();
+
+        /// The maximum number of claims made in a single block.
+        const MaxClaimsPerBlock: u32 = T::MaxClaimsPerBlock::get();
+
+        /// Identifier from which the accounts of organizations are derived.
+        const ModuleId: ModuleId = T::ModuleId::get();
+
+        /// The number of blocks during which an offered claim can be accepted.
+        const OfferExpiry: T::BlockNumber = T::OfferExpiry::get();
+
+        /// The number of blocks between a request to revoke a claim and its removal.
+        const RevokeDelay: T::BlockNumber = T::RevokeDelay::get();
+
+        /// The amount reserved from the owner of a namespace.
+        const NamespaceDeposit: BalanceOf<T, I> = T::NamespaceDeposit::get();
+
+        /// The number of blocks after which a claim expires unless it is renewed.
+        const ClaimPeriod: T::BlockNumber = T::ClaimPeriod::get();
+
+        /// The fee paid per block a claim is renewed for.
+        const RenewalFeePerBlock: BalanceOf<T, I> = T::RenewalFeePerBlock::get();
+
+        fn on_runtime_upgrade() -> Weight {
+            migration::migrate::<T, I>()
+        }
+
+        /// Remove the claims whose revocation was requested `RevokeDelay` blocks ago and has
+        /// not been cancelled since, then the claims reaching the end of their period.
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            UnverifiedClaims::<T, I>::kill();
+
+            let proofs = RevocationQueue::<T, I>::take(now);
+            let mut revoked = 0;
+            for (namespace, proof) in &proofs {
+                if PendingRevocations::<T, I>::get(namespace, proof) != Some(now) {
+                    continue;
+                }
+                PendingRevocations::<T, I>::remove(namespace, proof);
+                let reason = RevocationReasons::<T, I>::take(namespace, proof);
+                if let Some(claim) = Proofs::<T, I>::get(namespace, proof) {
+                    if !SupersededBy::<T, I>::contains_key(namespace, proof) {
+                        Self::remove_claim(*namespace, proof, &claim, reason);
+                        Self::deposit_event(RawEvent::ClaimRevoked(claim.owner, *namespace, proof.clone()));
+                        revoked += 1;
+                    }
+                }
+            }
+
+            let expiring = ExpiryQueue::<T, I>::take(now);
+            let mut expired = 0;
+            for (namespace, proof) in &expiring {
+                if Expiries::<T, I>::get(namespace, proof) != Some(now) {
+                    continue;
+                }
+                if let Some(claim) = Proofs::<T, I>::get(namespace, proof) {
+                    Self::expire_claim(*namespace, proof, &claim);
+                    Self::deposit_event(RawEvent::ClaimExpired(claim.owner, *namespace, proof.clone()));
+                    expired += 1;
+                }
+            }
+
+            T::DbWeight::get().reads_writes(
+                2 + 4 * proofs.len() as Weight + 2 * expiring.len() as Weight,
+                3 + 8 * revoked + 8 * expired,
+            )
+        }
+
+        /// Verify the documents behind the claims created in the block, if the node has a
+        /// gateway to fetch them from.
+        fn offchain_worker(_now: T::BlockNumber) {
+            offchain::verify_new_claims::<T, I>();
+        }
+
+        /// Pause or unpause the registry. While paused, all other calls fail.
+        ///
+        /// Revocations requested before the pause still take effect when due.
+        #[weight = 10_000]
+        fn set_paused(origin, paused: bool) {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            Paused::<I>::put(paused);
+
+            Self::deposit_event(RawEvent::PausedSet(paused));
+        }
+
+        /// Replace the accounts allowed to report verifications.
+        ///
+        /// At most `MaxVerifications` verifiers can be set, so that each of them can report on
+        /// every claim.
+        #[weight = 10_000 + T::DbWeight::get().writes(1)]
+        fn set_verifiers(origin, verifiers: Vec<T::AccountId>) {
+            T::VerifierManagerOrigin::ensure_origin(origin)?;
+            ensure!(verifiers.len() <= T::MaxVerifications::get() as usize, Error::<T, I>::TooManyVerifiers);
+
+            let mut verifiers = verifiers;
+            verifiers.sort();
+            verifiers.dedup();
+            Verifiers::<T, I>::put(&verifiers);
+
+            Self::deposit_event(RawEvent::VerifiersSet(verifiers));
+        }
+
+        /// Allow a user to claim ownership of an unclaimed proof, optionally describing it.
+        ///
+        /// `ClaimDeposit` and the deposit for the metadata are reserved from the sender until
+        /// the claim is revoked.
+        #[weight = 10_000]
+        fn create_claim(origin, namespace: NamespaceId, proof: T::Proof, metadata: Option<ClaimMetadata>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            Self::do_create_claim(sender, namespace, proof, metadata.unwrap_or_default(), None)?;
+        }
+
+        /// Allow a user to claim an unclaimed proof derived with `algorithm`, recording the
+        /// algorithm with the claim.
+        #[weight = 10_000]
+        fn create_claim_with_algorithm(
+            origin,
+            namespace: NamespaceId,
+            proof: T::Proof,
+            algorithm: HashAlgorithm,
+            metadata: Option<ClaimMetadata>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            Self::do_create_claim(sender, namespace, proof, metadata.unwrap_or_default(), Some(algorithm))?;
+        }
+
+        /// Allow a user to claim the proof of `content`, hashed on-chain with `algorithm`.
+        ///
+        /// The proof is made from the digest by `ProofType::from_digest`. Only documents up to
+        /// `MaxContentLength` bytes can be submitted.
+        #[weight = 10_000 + (content.len() as Weight).saturating_mul(WEIGHT_PER_CONTENT_BYTE)]
+        fn create_claim_from_content(
+            origin,
+            namespace: NamespaceId,
+            algorithm: HashAlgorithm,
+            content: Vec<u8>,
+            metadata: Option<ClaimMetadata>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+            ensure!(content.len() <= T::MaxContentLength::get() as usize, Error::<T, I>::ContentTooLong);
+
+            let digest = algorithm.hash(&content);
+            let proof = T::Proof::from_digest(algorithm, &digest).ok_or(Error::<T, I>::InvalidProofFormat)?;
+            Self::do_create_claim(sender, namespace, proof, metadata.unwrap_or_default(), Some(algorithm))?;
+        }
+
+        /// Allow a user to claim the digest of `content` computed with `algorithm`, and have
+        /// nodes keep the content in their offchain database.
+        ///
+        /// Only the proof goes into the state. The content is indexed under
+        /// `offchain_document_key` by nodes running with offchain indexing enabled, and
+        /// dropped from their database when the claim is removed. Only documents up to
+        /// `MaxIndexedDocument` bytes can be submitted.
+        #[weight = 10_000 + (content.len() as Weight).saturating_mul(WEIGHT_PER_CONTENT_BYTE)]
+        fn create_claim_with_content(
+            origin,
+            namespace: NamespaceId,
+            algorithm: HashAlgorithm,
+            content: Vec<u8>,
+            metadata: Option<ClaimMetadata>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+            ensure!(content.len() <= T::MaxIndexedDocument::get() as usize, Error::<T, I>::DocumentTooLong);
+
+            let digest = algorithm.hash(&content);
+            let proof = T::Proof::from_digest(algorithm, &digest).ok_or(Error::<T, I>::InvalidProofFormat)?;
+            let key = Self::offchain_document_key(namespace, &proof);
+            Self::do_create_claim(sender, namespace, proof, metadata.unwrap_or_default(), Some(algorithm))?;
+            sp_io::offchain_index::set(&key, &content);
+        }
+
+        /// Allow a user to claim the proof of `document`, hashed on-chain with `ProofHashing`,
+        /// and keep the document itself in storage alongside the claim.
+        ///
+        /// `DocumentDepositPerByte` is reserved for each byte of the document until the claim
+        /// is revoked. Only documents up to `MaxInlineDocument` bytes can be stored.
+        #[weight = 10_000 + (document.len() as Weight).saturating_mul(WEIGHT_PER_CONTENT_BYTE)]
+        #[transactional]
+        fn store_document(origin, namespace: NamespaceId, document: Vec<u8>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+            ensure!(document.len() <= T::MaxInlineDocument::get() as usize, Error::<T, I>::DocumentTooLong);
+
+            let algorithm = T::ProofHashing::get();
+            let proof = T::Proof::from_digest(algorithm, &algorithm.hash(&document))
+                .ok_or(Error::<T, I>::InvalidProofFormat)?;
+            Self::do_create_claim(sender.clone(), namespace, proof.clone(), ClaimMetadata::default(), Some(algorithm))?;
+
+            let deposit = T::DocumentDepositPerByte::get().saturating_mul((document.len() as u32).into());
+            T::Currency::reserve(&sender, deposit)?;
+            Documents::<T, I>::insert(namespace, &proof, (document, deposit));
+
+            Self::deposit_event(RawEvent::DocumentStored(sender, namespace, proof));
+        }
+
+        /// Allow a user to claim an unclaimed proof that cites already claimed proofs.
+        ///
+        /// The references are fixed at creation and indexed so that the citing claims of a
+        /// proof can be looked up.
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(references.len() as u64, references.len() as u64)]
+        fn create_claim_with_references(
+            origin,
+            namespace: NamespaceId,
+            proof: T::Proof,
+            metadata: Option<ClaimMetadata>,
+            references: Vec<T::Proof>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            ensure!(references.len() <= T::MaxReferences::get() as usize, Error::<T, I>::TooManyReferences);
+            for (i, reference) in references.iter().enumerate() {
+                ensure!(!references[..i].contains(reference), Error::<T, I>::DuplicateReference);
+                ensure!(Proofs::<T, I>::contains_key(namespace, reference), Error::<T, I>::NoSuchReference);
+            }
+
+            Self::do_create_claim(sender, namespace, proof.clone(), metadata.unwrap_or_default(), None)?;
+            for reference in &references {
+                CitedBy::<T, I>::insert((namespace, reference), &proof, ());
+            }
+            References::<T, I>::insert(namespace, &proof, references);
+        }
+
+        /// Allow the owner of a proof to register a revised version of the document.
+        ///
+        /// `new_proof` is claimed as with `create_claim` and linked to `old_proof`, which stays
+        /// claimed but is marked as superseded. Only the latest version can be amended, and not
+        /// while its revocation is pending.
         #[weight = 10_000]
-        fn create_claim(origin, proof: Vec<u8>) {
+        fn amend_claim(origin, namespace: NamespaceId, old_proof: T::Proof, new_proof: T::Proof) {
             let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
 
-            ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
-            let current_block = <frame_system::Module<T>>::block_number();
-            Proofs::<T>::insert(&proof, (&sender, current_block));
-            Self::deposit_event(RawEvent::ClaimCreated(sender, proof));
+            let old = Proofs::<T, I>::get(namespace, &old_proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &old.owner), Error::<T, I>::NotProofOwner);
+            ensure!(!SupersededBy::<T, I>::contains_key(namespace, &old_proof), Error::<T, I>::ClaimSuperseded);
+            ensure!(!PendingRevocations::<T, I>::contains_key(namespace, &old_proof), Error::<T, I>::RevocationPending);
 
+            Self::do_create_claim(old.owner.clone(), namespace, new_proof.clone(), ClaimMetadata::default(), None)?;
+            Supersedes::<T, I>::insert(namespace, &new_proof, &old_proof);
+            SupersededBy::<T, I>::insert(namespace, &old_proof, &new_proof);
+
+            Self::deposit_event(RawEvent::ClaimAmended(old.owner, namespace, old_proof, new_proof));
         }
 
+        /// Allow the owner of a proof to replace the metadata of the claim.
+        ///
+        /// The metadata deposit is topped up or partially released to match the new metadata.
         #[weight = 10_000]
-        fn revoke_claim(origin, proof: Vec<u8>) {
+        fn set_metadata(origin, namespace: NamespaceId, proof: T::Proof, metadata: ClaimMetadata) {
             let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let mut claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+            Self::ensure_valid_metadata(&metadata)?;
+
+            let metadata_deposit = Self::metadata_deposit(&metadata);
+            if metadata_deposit > claim.metadata_deposit {
+                T::Currency::reserve(&claim.owner, metadata_deposit - claim.metadata_deposit)?;
+            } else {
+                T::Currency::unreserve(&claim.owner, claim.metadata_deposit - metadata_deposit);
+            }
 
-            ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
-            let (owner, _) = Proofs::<T>::get(&proof);
-            ensure!(sender == owner, Error::<T>::NotProofOwner);
+            claim.metadata = metadata;
+            claim.metadata_deposit = metadata_deposit;
+            Proofs::<T, I>::insert(namespace, &proof, &claim);
+
+            Self::deposit_event(RawEvent::ClaimMetadataSet(claim.owner, namespace, proof));
+        }
 
-            Proofs::<T>::remove(&proof);
+        /// Allow the owner of a proof to revoke the claim and get the deposit back.
+        ///
+        /// Superseded versions cannot be revoked. Revoking the latest version of an amended
+        /// document makes the previous version the latest one again.
+        ///
+        /// Claims citing the proof keep listing it in their references, but it is no longer
+        /// indexed as cited.
+        ///
+        /// A `Tombstone` recording the claim and the optional `reason` is kept, and
+        /// `ReclaimPolicy` decides who may claim the proof again.
+        ///
+        /// Only available if `RevokeDelay` is zero; otherwise see `request_revoke`.
+        #[weight = 10_000]
+        fn revoke_claim(origin, namespace: NamespaceId, proof: T::Proof, reason: Option<Vec<u8>>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            ensure!(T::RevokeDelay::get().is_zero(), Error::<T, I>::RevocationDelayed);
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+            ensure!(!SupersededBy::<T, I>::contains_key(namespace, &proof), Error::<T, I>::ClaimSuperseded);
+            Self::ensure_valid_reason(&reason)?;
+
+            Self::remove_claim(namespace, &proof, &claim, reason);
+
+            Self::deposit_event(RawEvent::ClaimRevoked(claim.owner, namespace, proof));
+        }
+
+        /// Allow the owner of a proof to schedule the revocation of the claim `RevokeDelay`
+        /// blocks from now, leaving time to cancel it if the request was not genuine.
+        ///
+        /// The optional `reason` is recorded in the tombstone of the claim, as with
+        /// `revoke_claim`. The claim is revoked at once if `RevokeDelay` is zero.
+        #[weight = 10_000]
+        fn request_revoke(origin, namespace: NamespaceId, proof: T::Proof, reason: Option<Vec<u8>>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+            ensure!(!SupersededBy::<T, I>::contains_key(namespace, &proof), Error::<T, I>::ClaimSuperseded);
+            ensure!(!PendingRevocations::<T, I>::contains_key(namespace, &proof), Error::<T, I>::RevocationPending);
+            Self::ensure_valid_reason(&reason)?;
+
+            let delay = T::RevokeDelay::get();
+            if delay.is_zero() {
+                Self::remove_claim(namespace, &proof, &claim, reason);
+                Self::deposit_event(RawEvent::ClaimRevoked(claim.owner, namespace, proof));
+                return Ok(());
+            }
+
+            let at = <frame_system::Module<T>>::block_number().saturating_add(delay);
+            PendingRevocations::<T, I>::insert(namespace, &proof, at);
+            RevocationReasons::<T, I>::set(namespace, &proof, reason);
+            RevocationQueue::<T, I>::append(at, (namespace, &proof));
+
+            Self::deposit_event(RawEvent::ClaimPendingRevocation(claim.owner, namespace, proof, at));
+        }
+
+        /// Allow the owner of a proof to cancel a pending revocation of the claim.
+        #[weight = 10_000]
+        fn cancel_revoke(origin, namespace: NamespaceId, proof: T::Proof) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+            ensure!(PendingRevocations::<T, I>::contains_key(namespace, &proof), Error::<T, I>::NoPendingRevocation);
+
+            // The entry in `RevocationQueue` is skipped once it is due.
+            PendingRevocations::<T, I>::remove(namespace, &proof);
+            RevocationReasons::<T, I>::remove(namespace, &proof);
+
+            Self::deposit_event(RawEvent::RevocationCancelled(claim.owner, namespace, proof));
+        }
+
+        /// Allow the owner of a proof to extend the claim by `extra_blocks`, paying
+        /// `RenewalFeePerBlock` for each of them.
+        ///
+        /// Only claims of registries with a `ClaimPeriod` expire. Claims pending revocation
+        /// cannot be renewed, and `extra_blocks` must not be zero.
+        #[weight = 10_000]
+        fn renew_claim(origin, namespace: NamespaceId, proof: T::Proof, extra_blocks: T::BlockNumber) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+            ensure!(!PendingRevocations::<T, I>::contains_key(namespace, &proof), Error::<T, I>::RevocationPending);
+            let expires = Expiries::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::ClaimDoesNotExpire)?;
+            ensure!(expires > <frame_system::Module<T>>::block_number(), Error::<T, I>::ClaimExpired);
+
+            ensure!(!extra_blocks.is_zero(), Error::<T, I>::ZeroRenewal);
+            let blocks: u32 = extra_blocks.try_into().map_err(|_| Error::<T, I>::RenewalTooLong)?;
+            let renewed = expires.checked_add(&extra_blocks).ok_or(Error::<T, I>::RenewalTooLong)?;
+            let fee = T::RenewalFeePerBlock::get().saturating_mul(blocks.into());
+            let paid = T::Currency::withdraw(
+                &claim.owner,
+                fee,
+                WithdrawReasons::from(WithdrawReason::Fee),
+                ExistenceRequirement::KeepAlive,
+            )?;
+            T::OnRenewalFee::on_unbalanced(paid);
+
+            ExpiryQueue::<T, I>::mutate(expires, |queue| {
+                queue.retain(|(n, p)| *n != namespace || *p != proof)
+            });
+            ExpiryQueue::<T, I>::append(renewed, (namespace, &proof));
+            Expiries::<T, I>::insert(namespace, &proof, renewed);
+
+            Self::deposit_event(RawEvent::ClaimRenewed(claim.owner, namespace, proof, renewed, fee));
+        }
+
+        /// Allow the owner of a proof to offer the claim to another account.
+        ///
+        /// The claim only changes hands once `to` accepts it, within `OfferExpiry` blocks. A new
+        /// offer replaces any previous one.
+        #[weight = 10_000]
+        fn offer_claim(origin, namespace: NamespaceId, proof: T::Proof, to: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+
+            Self::do_offer_claim(namespace, proof, claim.owner, to);
+        }
+
+        /// Allow the recipient of an offer to take over the claim.
+        ///
+        /// The deposits are reserved from the new owner and released to the previous one.
+        #[weight = 10_000]
+        fn accept_claim(origin, namespace: NamespaceId, proof: T::Proof) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let offer = PendingTransfers::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchOffer)?;
+            ensure!(Self::can_manage(&sender, &offer.to), Error::<T, I>::NotOfferRecipient);
+            ensure!(
+                <frame_system::Module<T>>::block_number() <= offer.expires,
+                Error::<T, I>::OfferExpired
+            );
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+
+            Self::do_transfer_claim(namespace, proof, claim, offer.to)?;
+        }
+
+        /// Allow the owner of a proof to withdraw an offer, or its recipient to decline it.
+        ///
+        /// Expired offers are cleared the same way.
+        #[weight = 10_000]
+        fn cancel_offer(origin, namespace: NamespaceId, proof: T::Proof) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let offer = PendingTransfers::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchOffer)?;
+            let is_owner = Proofs::<T, I>::get(namespace, &proof)
+                .map_or(false, |claim| Self::can_manage(&sender, &claim.owner));
+            ensure!(is_owner || Self::can_manage(&sender, &offer.to), Error::<T, I>::NotProofOwner);
+
+            PendingTransfers::<T, I>::remove(namespace, &proof);
+
+            Self::deposit_event(RawEvent::OfferCancelled(namespace, proof));
+        }
+
+        /// Allow the owner of a proof to list the claim for sale, or to withdraw it from sale
+        /// by giving no price.
+        #[weight = 10_000]
+        fn set_price(origin, namespace: NamespaceId, proof: T::Proof, price: Option<BalanceOf<T, I>>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(Self::can_manage(&sender, &claim.owner), Error::<T, I>::NotProofOwner);
+
+            Prices::<T, I>::set(namespace, &proof, price);
+
+            Self::deposit_event(RawEvent::ClaimListed(claim.owner, namespace, proof, price));
+        }
+
+        /// Allow a user to buy a listed claim, paying its price to the owner.
+        ///
+        /// The purchase fails if the price is above `max_price`, so that the owner cannot raise
+        /// it while the purchase is pending. The deposits are reserved from the buyer and
+        /// released to the seller.
+        #[weight = 10_000]
+        #[transactional]
+        fn buy_claim(origin, namespace: NamespaceId, proof: T::Proof, max_price: BalanceOf<T, I>) {
+            let buyer = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            let price = Prices::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NotForSale)?;
+            ensure!(price <= max_price, Error::<T, I>::PriceTooHigh);
+            ensure!(buyer != claim.owner, Error::<T, I>::AlreadyOwner);
+
+            let seller = claim.owner.clone();
+            T::Currency::transfer(&buyer, &seller, price, ExistenceRequirement::KeepAlive)?;
+            Self::do_transfer_claim(namespace, proof.clone(), claim, buyer.clone())?;
+
+            Self::deposit_event(RawEvent::ClaimSold(seller, buyer, namespace, proof, price));
+        }
+
+        /// Allow the owner of a proof, or an operator of the owner, to approve an account to
+        /// transfer the claim. Giving no account clears the approval.
+        ///
+        /// The approval is cleared whenever the claim changes hands.
+        #[weight = 10_000]
+        fn approve(origin, namespace: NamespaceId, proof: T::Proof, operator: Option<T::AccountId>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(
+                Self::can_manage(&sender, &claim.owner)
+                    || OperatorApprovals::<T, I>::get(&claim.owner, &sender),
+                Error::<T, I>::NotApproved
+            );
+
+            Approvals::<T, I>::set(namespace, &proof, operator.clone());
+
+            Self::deposit_event(RawEvent::Approval(claim.owner, namespace, proof, operator));
+        }
+
+        /// Allow a user to approve or disapprove an operator to transfer, and approve others to
+        /// transfer, all claims of the user.
+        #[weight = 10_000]
+        fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            if approved {
+                OperatorApprovals::<T, I>::insert(&sender, &operator, true);
+            } else {
+                OperatorApprovals::<T, I>::remove(&sender, &operator);
+            }
+
+            Self::deposit_event(RawEvent::ApprovalForAll(sender, operator, approved));
+        }
+
+        /// Allow an account approved by the owner of a proof to hand the claim over to another
+        /// account. Owners offer their claims through `offer_claim` instead.
+        ///
+        /// The claim changes hands at once if the sender takes it over itself, the deposits
+        /// being reserved from the sender and released to the previous owner. Any other account
+        /// is offered the claim as with `offer_claim`, so that nobody takes on the deposits of a
+        /// claim without accepting it.
+        #[weight = 10_000]
+        fn transfer_from(origin, namespace: NamespaceId, proof: T::Proof, to: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let claim = Proofs::<T, I>::get(namespace, &proof).ok_or(Error::<T, I>::NoSuchProof)?;
+            ensure!(
+                Approvals::<T, I>::get(namespace, &proof).as_ref() == Some(&sender)
+                    || OperatorApprovals::<T, I>::get(&claim.owner, &sender),
+                Error::<T, I>::NotApproved
+            );
+
+            if to == sender {
+                Self::do_transfer_claim(namespace, proof, claim, to)?;
+            } else {
+                Self::do_offer_claim(namespace, proof, claim.owner, to);
+            }
+        }
+
+        /// Create an organization administered by the sender.
+        ///
+        /// Claims of the organization are owned by, and their deposits reserved from, the
+        /// account returned by `org_account_id`.
+        #[weight = 10_000]
+        fn create_org(origin) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let org = NextOrgId::<I>::get();
+            let next = org.checked_add(1).ok_or(Error::<T, I>::OrgIdOverflow)?;
+            NextOrgId::<I>::put(next);
+
+            let account = Self::org_account_id(org);
+            OrgOfAccount::<T, I>::insert(&account, org);
+            OrgMembers::<T, I>::insert(org, &sender, OrgRole::Admin);
+            OrgAdmins::<I>::insert(org, 1);
+
+            Self::deposit_event(RawEvent::OrgCreated(org, account, sender.clone()));
+            Self::deposit_event(RawEvent::OrgMemberAdded(org, sender, OrgRole::Admin));
+        }
+
+        /// Allow an admin of an organization to add a member or change the role of a member.
+        #[weight = 10_000]
+        fn add_org_member(origin, org: OrgId, who: T::AccountId, role: OrgRole) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let admins = Self::ensure_org_admin(org, &sender)?;
+            let previous = OrgMembers::<T, I>::get(org, &who);
+            let admins = match (previous, role) {
+                (Some(OrgRole::Admin), OrgRole::Claimer) => {
+                    ensure!(admins > 1, Error::<T, I>::LastOrgAdmin);
+                    admins - 1
+                }
+                (Some(OrgRole::Admin), OrgRole::Admin) => admins,
+                (_, OrgRole::Admin) => admins + 1,
+                (_, OrgRole::Claimer) => admins,
+            };
+
+            OrgMembers::<T, I>::insert(org, &who, role);
+            OrgAdmins::<I>::insert(org, admins);
+
+            Self::deposit_event(RawEvent::OrgMemberAdded(org, who, role));
+        }
+
+        /// Remove a member from an organization.
+        ///
+        /// Admins may remove any member, and every member may leave.
+        #[weight = 10_000]
+        fn remove_org_member(origin, org: OrgId, who: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let admins = OrgAdmins::<I>::get(org).ok_or(Error::<T, I>::NoSuchOrg)?;
+            if sender != who {
+                Self::ensure_org_admin(org, &sender)?;
+            }
+            let role = OrgMembers::<T, I>::get(org, &who).ok_or(Error::<T, I>::NotOrgMember)?;
+            if role == OrgRole::Admin {
+                ensure!(admins > 1, Error::<T, I>::LastOrgAdmin);
+                OrgAdmins::<I>::insert(org, admins - 1);
+            }
+            OrgMembers::<T, I>::remove(org, &who);
+
+            Self::deposit_event(RawEvent::OrgMemberRemoved(org, who));
+        }
+
+        /// Allow a member of an organization to claim an unclaimed proof on its behalf.
+        ///
+        /// The deposits are reserved from the account of the organization.
+        #[weight = 10_000]
+        fn create_claim_as_org(
+            origin,
+            org: OrgId,
+            namespace: NamespaceId,
+            proof: T::Proof,
+            metadata: Option<ClaimMetadata>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            ensure!(OrgAdmins::<I>::contains_key(org), Error::<T, I>::NoSuchOrg);
+            ensure!(OrgMembers::<T, I>::contains_key(org, &sender), Error::<T, I>::NotOrgMember);
+
+            let owner = Self::org_account_id(org);
+            Self::do_create_claim(owner, namespace, proof, metadata.unwrap_or_default(), None)?;
+        }
+
+        /// Register a namespace owned by the sender, within which only the owner can create
+        /// claims.
+        ///
+        /// `NamespaceDeposit` is reserved from the owner until the namespace is deregistered.
+        /// Namespaces can be offered to the account of an organization, so that its members
+        /// claim proofs in it through `create_claim_as_org`.
+        #[weight = 10_000]
+        fn register_namespace(origin) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let namespace = NextNamespaceId::<I>::get();
+            let next = namespace.checked_add(1).ok_or(Error::<T, I>::NamespaceIdOverflow)?;
+            let deposit = T::NamespaceDeposit::get();
+            T::Currency::reserve(&sender, deposit)?;
+
+            NextNamespaceId::<I>::put(next);
+            Namespaces::<T, I>::insert(namespace, (&sender, deposit));
+
+            Self::deposit_event(RawEvent::NamespaceRegistered(namespace, sender));
+        }
+
+        /// Allow the owner of a namespace to offer it to another account.
+        ///
+        /// The namespace only changes hands once `to` accepts it, within `OfferExpiry` blocks.
+        /// A new offer replaces any previous one.
+        #[weight = 10_000]
+        fn offer_namespace(origin, namespace: NamespaceId, to: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let (owner, _) = Namespaces::<T, I>::get(namespace).ok_or(Error::<T, I>::NoSuchNamespace)?;
+            ensure!(Self::can_manage(&sender, &owner), Error::<T, I>::NotNamespaceOwner);
+
+            let expires = <frame_system::Module<T>>::block_number().saturating_add(T::OfferExpiry::get());
+            PendingNamespaceTransfers::<T, I>::insert(namespace, PendingTransfer { to: to.clone(), expires });
+
+            Self::deposit_event(RawEvent::NamespaceOffered(namespace, owner, to));
+        }
+
+        /// Allow the recipient of an offer to take over the namespace.
+        ///
+        /// The deposit is reserved from the new owner and released to the previous one. The
+        /// claims already made in the namespace keep their owners.
+        #[weight = 10_000]
+        fn accept_namespace(origin, namespace: NamespaceId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let offer = PendingNamespaceTransfers::<T, I>::get(namespace).ok_or(Error::<T, I>::NoSuchOffer)?;
+            ensure!(Self::can_manage(&sender, &offer.to), Error::<T, I>::NotOfferRecipient);
+            ensure!(
+                <frame_system::Module<T>>::block_number() <= offer.expires,
+                Error::<T, I>::OfferExpired
+            );
+            let (owner, deposit) = Namespaces::<T, I>::get(namespace).ok_or(Error::<T, I>::NoSuchNamespace)?;
+
+            T::Currency::reserve(&offer.to, deposit)?;
+            T::Currency::unreserve(&owner, deposit);
+            Namespaces::<T, I>::insert(namespace, (&offer.to, deposit));
+            PendingNamespaceTransfers::<T, I>::remove(namespace);
+
+            Self::deposit_event(RawEvent::NamespaceTransferred(namespace, owner, offer.to));
+        }
+
+        /// Allow the owner of a namespace to give it up, releasing its deposit.
+        ///
+        /// The claims already made in the namespace are kept, but no new claim can be made in
+        /// it. Namespace identifiers are never reused.
+        #[weight = 10_000]
+        fn deregister_namespace(origin, namespace: NamespaceId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+
+            let (owner, deposit) = Namespaces::<T, I>::get(namespace).ok_or(Error::<T, I>::NoSuchNamespace)?;
+            ensure!(Self::can_manage(&sender, &owner), Error::<T, I>::NotNamespaceOwner);
+
+            T::Currency::unreserve(&owner, deposit);
+            Namespaces::<T, I>::remove(namespace);
+            PendingNamespaceTransfers::<T, I>::remove(namespace);
+
+            Self::deposit_event(RawEvent::NamespaceDeregistered(namespace, owner));
+        }
+
+        /// Record whether the document behind the claim on `proof` hashes to it, as checked by
+        /// the sender.
+        ///
+        /// Submitted by the offchain worker of a node whose key `VerifierOrigin` admits. A later
+        /// report of the same verifier replaces the earlier one. Reports of at most
+        /// `MaxVerifications` verifiers are kept per claim.
+        #[weight = 10_000]
+        fn report_verification(origin, namespace: NamespaceId, proof: T::Proof, ok: bool) {
+            let verifier = T::VerifierOrigin::ensure_origin(origin)?;
+            ensure!(!Self::paused(), Error::<T, I>::PalletPaused);
+            ensure!(Proofs::<T, I>::contains_key(namespace, &proof), Error::<T, I>::NoSuchProof);
+
+            let mut reports = Verifications::<T, I>::get(namespace, &proof);
+            match reports.iter_mut().find(|(who, _)| *who == verifier) {
+                Some(report) => report.1 = ok,
+                None => {
+                    ensure!(
+                        reports.len() < T::MaxVerifications::get() as usize,
+                        Error::<T, I>::TooManyVerifications
+                    );
+                    reports.push((verifier.clone(), ok));
+                }
+            }
+            Verifications::<T, I>::insert(namespace, &proof, reports);
+
+            Self::deposit_event(RawEvent::ClaimVerified(verifier, namespace, proof, ok));
+        }
+    }
+}
+
+/// The verifiers of the registry, so that `VerifierOrigin` can be
+/// `EnsureSignedBy<Module<T, I>, T::AccountId>`.
+impl<T: Trait<I>, I: Instance> Contains<T::AccountId> for Module<T, I> {
+    fn sorted_members() -> Vec<T::AccountId> {
+        Self::verifiers()
+    }
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// All versions of the document `proof` is a version of in `namespace`, oldest first.
+    ///
+    /// Empty if `proof` is not claimed.
+    pub fn version_history(namespace: NamespaceId, proof: T::Proof) -> Vec<T::Proof> {
+        if !Proofs::<T, I>::contains_key(namespace, &proof) {
+            return Vec::new();
+        }
+
+        let mut first = proof;
+        while let Some(previous) = Supersedes::<T, I>::get(namespace, &first) {
+            first = previous;
+        }
+
+        let mut history = Vec::new();
+        let mut next = Some(first);
+        while let Some(version) = next {
+            next = SupersededBy::<T, I>::get(namespace, &version);
+            history.push(version);
+        }
+        history
+    }
+
+    /// The claim in `namespace` on the document whose `digest` was computed with `algorithm`.
+    ///
+    /// Claims recording another algorithm are ignored; claims recording none are assumed to
+    /// match.
+    pub fn claim_of_digest(
+        namespace: NamespaceId,
+        algorithm: HashAlgorithm,
+        digest: &[u8; 32],
+    ) -> Option<ClaimOf<T, I>> {
+        let proof = T::Proof::from_digest(algorithm, digest)?;
+        Self::proofs(namespace, proof)
+            .filter(|claim| claim.algorithm.map_or(true, |a| a == algorithm))
+    }
+
+    /// The claim in `namespace` on `content` hashed with `algorithm`, as claimed by
+    /// `create_claim_from_content`.
+    pub fn claim_of_content(
+        namespace: NamespaceId,
+        algorithm: HashAlgorithm,
+        content: &[u8],
+    ) -> Option<ClaimOf<T, I>> {
+        Self::claim_of_digest(namespace, algorithm, &algorithm.hash(content))
+    }
+
+    /// The key of the offchain database under which the content claimed on `proof` in
+    /// `namespace` by `create_claim_with_content` is indexed.
+    ///
+    /// Keys include the storage prefix of the instance, so that registries do not share
+    /// documents.
+    pub fn offchain_document_key(namespace: NamespaceId, proof: &T::Proof) -> Vec<u8> {
+        (OFFCHAIN_DOCUMENT_PREFIX, I::PREFIX, namespace, proof).encode()
+    }
+
+    /// Whether the document behind the claim on `proof` in `namespace` matched it, as reported
+    /// by `verifier`.
+    pub fn verification(
+        namespace: NamespaceId,
+        proof: &T::Proof,
+        verifier: &T::AccountId,
+    ) -> Option<bool> {
+        Self::verifications(namespace, proof)
+            .into_iter()
+            .find(|(who, _)| who == verifier)
+            .map(|(_, ok)| ok)
+    }
+
+    /// The topic under which the events concerning the claim on `proof` in `namespace` are
+    /// indexed: the hash of `(I::PREFIX, namespace, proof)` in the SCALE encoding, so that
+    /// registries storing the same proofs do not share topics.
+    pub fn proof_topic(namespace: NamespaceId, proof: &T::Proof) -> T::Hash {
+        T::Hashing::hash_of(&(I::PREFIX, namespace, proof))
+    }
+
+    /// The topic under which the events concerning the claims of `who` are indexed: the hash
+    /// of the account in the SCALE encoding.
+    pub fn account_topic(who: &T::AccountId) -> T::Hash {
+        T::Hashing::hash_of(who)
+    }
+
+    /// The document stored on-chain with the claim on `proof` in `namespace`, if any.
+    pub fn document(namespace: NamespaceId, proof: T::Proof) -> Option<Vec<u8>> {
+        Documents::<T, I>::get(namespace, proof).map(|(document, _)| document)
+    }
+
+    /// Whether `call` may be dispatched in the current state of the registry, for use in the
+    /// `BaseCallFilter` of the runtime.
+    pub fn is_call_allowed(call: &Call<T, I>) -> bool {
+        !Self::paused() || matches!(call, Call::set_paused(..))
+    }
+
+    /// The counters of the registry.
+    pub fn stats() -> ClaimStats {
+        ClaimStats {
+            count: Self::claim_count(),
+            created: Self::claims_created_total(),
+            revoked: Self::claims_revoked_total(),
+            expired: Self::claims_expired_total(),
+        }
+    }
+
+    /// The claims made from block `from` to block `to`, starting at `cursor` if given and
+    /// returning at most `limit` claims.
+    ///
+    /// Pass the `next` cursor of a page to fetch the following one. A page may hold fewer than
+    /// `limit` claims and still have a successor, as at most `MAX_BLOCKS_PER_PAGE` blocks are
+    /// searched for each page. A `limit` of zero is taken as one, so that paging always
+    /// progresses.
+    pub fn claims_in_range(
+        from: T::BlockNumber,
+        to: T::BlockNumber,
+        cursor: Option<(T::BlockNumber, u32)>,
+        limit: u32,
+    ) -> ClaimsPage<T::BlockNumber, T::Proof> {
+        let limit = limit.max(1);
+        let (mut block, mut skip) = cursor
+            .filter(|(block, _)| *block >= from)
+            .unwrap_or((from, 0));
+        let mut claims = Vec::new();
+        let mut searched = 0;
+        while block <= to {
+            if searched == MAX_BLOCKS_PER_PAGE {
+                return ClaimsPage {
+                    claims,
+                    next: Some((block, 0)),
+                };
+            }
+            let made = ClaimsByBlock::<T, I>::get(block);
+            for (index, (namespace, proof)) in made.into_iter().enumerate().skip(skip as usize) {
+                if claims.len() == limit as usize {
+                    return ClaimsPage {
+                        claims,
+                        next: Some((block, index as u32)),
+                    };
+                }
+                claims.push((block, namespace, proof));
+            }
+            if block == to {
+                break;
+            }
+            block += One::one();
+            skip = 0;
+            searched += 1;
+        }
+        ClaimsPage { claims, next: None }
+    }
+
+    /// The account owning the claims of organization `org`.
+    pub fn org_account_id(org: OrgId) -> T::AccountId {
+        T::ModuleId::get().into_sub_account(org)
+    }
+
+    /// The claims citing `proof` in `namespace`.
+    pub fn cited_by(namespace: NamespaceId, proof: T::Proof) -> Vec<T::Proof> {
+        CitedBy::<T, I>::iter_prefix((namespace, proof))
+            .map(|(citing, ())| citing)
+            .collect()
+    }
+
+    /// Claim `proof` in `namespace`, derived with `algorithm` if known, for `owner`, reserving
+    /// the deposits from `owner`.
+    ///
+    /// Only the owner of a registered namespace, or an account managing it, can own new claims
+    /// in it.
+    fn do_create_claim(
+        owner: T::AccountId,
+        namespace: NamespaceId,
+        proof: T::Proof,
+        metadata: ClaimMetadata,
+        algorithm: Option<HashAlgorithm>,
+    ) -> dispatch::DispatchResult {
+        let bytes = proof.as_ref();
+        ensure!(
+            bytes.len() >= T::MinProofLength::get() as usize,
+            Error::<T, I>::ProofTooShort
+        );
+        ensure!(
+            bytes.len() <= T::MaxProofLength::get() as usize,
+            Error::<T, I>::ProofTooLong
+        );
+        ensure!(
+            T::ProofValidator::is_valid(bytes),
+            Error::<T, I>::InvalidProofFormat
+        );
+        if namespace != PUBLIC_NAMESPACE {
+            let (namespace_owner, _) =
+                Namespaces::<T, I>::get(namespace).ok_or(Error::<T, I>::NoSuchNamespace)?;
+            ensure!(
+                Self::can_manage(&owner, &namespace_owner),
+                Error::<T, I>::NotNamespaceOwner
+            );
+        }
+        ensure!(
+            !Proofs::<T, I>::contains_key(namespace, &proof),
+            Error::<T, I>::ProofAlreadyClaimed
+        );
+        if let Some(tombstone) = Tombstones::<T, I>::get(namespace, &proof) {
+            let allowed = match T::ReclaimPolicy::get() {
+                ReclaimPolicy::Allow => true,
+                ReclaimPolicy::Deny => false,
+                ReclaimPolicy::OwnerOnly => owner == tombstone.owner,
+            };
+            ensure!(allowed, Error::<T, I>::ProofTombstoned);
+        }
+        Self::ensure_valid_metadata(&metadata)?;
+        let current_block = <frame_system::Module<T>>::block_number();
+        ensure!(
+            ClaimsByBlock::<T, I>::decode_len(current_block).unwrap_or(0)
+                < T::MaxClaimsPerBlock::get() as usize,
+            Error::<T, I>::TooManyClaimsInBlock
+        );
+
+        let deposit = T::ClaimDeposit::get();
+        let metadata_deposit = Self::metadata_deposit(&metadata);
+        T::Currency::reserve(&owner, deposit.saturating_add(metadata_deposit))?;
+
+        if metadata.uri.is_some() {
+            UnverifiedClaims::<T, I>::append((namespace, &proof));
+        }
+        ClaimsByBlock::<T, I>::append(current_block, (namespace, &proof));
+        Proofs::<T, I>::insert(
+            namespace,
+            &proof,
+            Claim {
+                owner: owner.clone(),
+                block_number: current_block,
+                deposit,
+                metadata,
+                metadata_deposit,
+                algorithm,
+            },
+        );
+        let period = T::ClaimPeriod::get();
+        if !period.is_zero() {
+            let expires = current_block.saturating_add(period);
+            Expiries::<T, I>::insert(namespace, &proof, expires);
+            ExpiryQueue::<T, I>::append(expires, (namespace, &proof));
+        }
+        ClaimCount::<I>::mutate(|count| *count = count.saturating_add(1));
+        ClaimsCreatedTotal::<I>::mutate(|total| *total = total.saturating_add(1));
+        OwnerClaimCount::<T, I>::mutate(&owner, |count| *count = count.saturating_add(1));
+        T::OnClaimCreated::on_claim_created(&owner, proof.as_ref());
+        Self::deposit_event(RawEvent::ClaimCreated(owner, namespace, proof));
+        Ok(())
+    }
+
+    /// Deposit `event`, indexed under the topics of the claims and accounts it concerns so that
+    /// clients can find it without scanning every event.
+    fn deposit_event(event: Event<T, I>) {
+        let topics = Self::event_topics(&event);
+        let event = <T as Trait<I>>::Event::from(event).into();
+        <frame_system::Module<T>>::deposit_event_indexed(&topics, event);
+    }
+
+    /// The topics `event` is indexed under: those of the proofs and of the accounts involved.
+    fn event_topics(event: &Event<T, I>) -> Vec<T::Hash> {
+        use RawEvent::*;
+        match event {
+            ClaimCreated(who, namespace, proof)
+            | ClaimRevoked(who, namespace, proof)
+            | ClaimPendingRevocation(who, namespace, proof, _)
+            | RevocationCancelled(who, namespace, proof)
+            | ClaimExpired(who, namespace, proof)
+            | ClaimRenewed(who, namespace, proof, _, _)
+            | ClaimMetadataSet(who, namespace, proof)
+            | Approval(who, namespace, proof, _)
+            | ClaimListed(who, namespace, proof, _)
+            | DocumentStored(who, namespace, proof)
+            | ClaimVerified(who, namespace, proof, _) => vec![
+                Self::proof_topic(*namespace, proof),
+                Self::account_topic(who),
+            ],
+            ClaimAmended(who, namespace, old, new) => vec![
+                Self::proof_topic(*namespace, old),
+                Self::proof_topic(*namespace, new),
+                Self::account_topic(who),
+            ],
+            ClaimOffered(from, to, namespace, proof)
+            | ClaimTransferred(from, to, namespace, proof)
+            | ClaimSold(from, to, namespace, proof, _) => vec![
+                Self::proof_topic(*namespace, proof),
+                Self::account_topic(from),
+                Self::account_topic(to),
+            ],
+            OfferCancelled(namespace, proof) => vec![Self::proof_topic(*namespace, proof)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether `who` may act on a claim owned by `owner`: either as the owner itself or as an
+    /// admin of the organization owning the claim.
+    fn can_manage(who: &T::AccountId, owner: &T::AccountId) -> bool {
+        who == owner
+            || Self::org_of_account(owner).map_or(false, |org| {
+                Self::org_member(org, who) == Some(OrgRole::Admin)
+            })
+    }
+
+    /// Ensure `who` is an admin of `org`, returning the number of admins of `org`.
+    fn ensure_org_admin(org: OrgId, who: &T::AccountId) -> Result<u32, dispatch::DispatchError> {
+        let admins = OrgAdmins::<I>::get(org).ok_or(Error::<T, I>::NoSuchOrg)?;
+        ensure!(
+            OrgMembers::<T, I>::get(org, who) == Some(OrgRole::Admin),
+            Error::<T, I>::InsufficientOrgRole
+        );
+        Ok(admins)
+    }
+
+    /// Offer the claim of `owner` on `proof` in `namespace` to `to` for `OfferExpiry` blocks,
+    /// replacing any previous offer.
+    fn do_offer_claim(
+        namespace: NamespaceId,
+        proof: T::Proof,
+        owner: T::AccountId,
+        to: T::AccountId,
+    ) {
+        let expires =
+            <frame_system::Module<T>>::block_number().saturating_add(T::OfferExpiry::get());
+        PendingTransfers::<T, I>::insert(
+            namespace,
+            &proof,
+            PendingTransfer {
+                to: to.clone(),
+                expires,
+            },
+        );
+
+        Self::deposit_event(RawEvent::ClaimOffered(owner, to, namespace, proof));
+    }
+
+    /// Hand `claim` over to `to`, moving its deposits from the previous owner to `to`.
+    ///
+    /// Any offer, listing, approval or pending revocation of the claim made by the previous
+    /// owner is dropped.
+    fn do_transfer_claim(
+        namespace: NamespaceId,
+        proof: T::Proof,
+        mut claim: ClaimOf<T, I>,
+        to: T::AccountId,
+    ) -> dispatch::DispatchResult {
+        let deposit = Self::claim_deposit(namespace, &proof, &claim);
+        T::Currency::reserve(&to, deposit)?;
+        T::Currency::unreserve(&claim.owner, deposit);
+
+        let from = sp_std::mem::replace(&mut claim.owner, to.clone());
+        Proofs::<T, I>::insert(namespace, &proof, claim);
+        OwnerClaimCount::<T, I>::mutate(&from, |count| *count = count.saturating_sub(1));
+        OwnerClaimCount::<T, I>::mutate(&to, |count| *count = count.saturating_add(1));
+        PendingTransfers::<T, I>::remove(namespace, &proof);
+        Prices::<T, I>::remove(namespace, &proof);
+        Approvals::<T, I>::remove(namespace, &proof);
+        PendingRevocations::<T, I>::remove(namespace, &proof);
+        RevocationReasons::<T, I>::remove(namespace, &proof);
+        T::OnClaimTransferred::on_claim_transferred(&from, &to, proof.as_ref());
+
+        Self::deposit_event(RawEvent::ClaimTransferred(from, to, namespace, proof));
+        Ok(())
+    }
+
+    /// Remove the claim on `proof` in `namespace` and everything attached to it, and release
+    /// its deposits.
+    ///
+    /// The claim is recorded in `Tombstones`, replacing any earlier tombstone of `proof`.
+    fn remove_claim(
+        namespace: NamespaceId,
+        proof: &T::Proof,
+        claim: &ClaimOf<T, I>,
+        reason: Option<Vec<u8>>,
+    ) {
+        Self::clear_claim(namespace, proof, claim);
+        Tombstones::<T, I>::insert(
+            namespace,
+            proof,
+            Tombstone {
+                owner: claim.owner.clone(),
+                claimed_at: claim.block_number,
+                revoked_at: <frame_system::Module<T>>::block_number(),
+                reason,
+            },
+        );
+        ClaimsRevokedTotal::<I>::mutate(|total| *total = total.saturating_add(1));
+        T::OnClaimRevoked::on_claim_revoked(&claim.owner, proof.as_ref());
+    }
+
+    /// Remove the claim on `proof` in `namespace` at the end of its period.
+    ///
+    /// Unlike a revocation, no tombstone is left, so the proof can be claimed again by anyone.
+    fn expire_claim(namespace: NamespaceId, proof: &T::Proof, claim: &ClaimOf<T, I>) {
+        Self::clear_claim(namespace, proof, claim);
+        ClaimsExpiredTotal::<I>::mutate(|total| *total = total.saturating_add(1));
+    }
+
+    /// Remove the claim on `proof` in `namespace` with everything attached to it and release
+    /// its deposits.
+    fn clear_claim(namespace: NamespaceId, proof: &T::Proof, claim: &ClaimOf<T, I>) {
+        Proofs::<T, I>::remove(namespace, proof);
+        if let Some(previous) = Supersedes::<T, I>::take(namespace, proof) {
+            SupersededBy::<T, I>::remove(namespace, &previous);
+        }
+        if let Some(next) = SupersededBy::<T, I>::take(namespace, proof) {
+            Supersedes::<T, I>::remove(namespace, &next);
+        }
+        for reference in References::<T, I>::take(namespace, proof) {
+            CitedBy::<T, I>::remove((namespace, &reference), proof);
+        }
+        CitedBy::<T, I>::remove_prefix((namespace, proof));
+        Verifications::<T, I>::remove(namespace, proof);
+        ClaimsByBlock::<T, I>::mutate_exists(claim.block_number, |claims| {
+            if let Some(made) = claims {
+                made.retain(|(n, p)| *n != namespace || p != proof);
+                if made.is_empty() {
+                    *claims = None;
+                }
+            }
+        });
+        PendingTransfers::<T, I>::remove(namespace, proof);
+        Prices::<T, I>::remove(namespace, proof);
+        Approvals::<T, I>::remove(namespace, proof);
+        PendingRevocations::<T, I>::remove(namespace, proof);
+        RevocationReasons::<T, I>::remove(namespace, proof);
+        Expiries::<T, I>::remove(namespace, proof);
+
+        T::Currency::unreserve(&claim.owner, Self::claim_deposit(namespace, proof, claim));
+        Documents::<T, I>::remove(namespace, proof);
+        sp_io::offchain_index::clear(&Self::offchain_document_key(namespace, proof));
+        ClaimCount::<I>::mutate(|count| *count = count.saturating_sub(1));
+        OwnerClaimCount::<T, I>::mutate(&claim.owner, |count| *count = count.saturating_sub(1));
+    }
+
+    fn ensure_valid_metadata(metadata: &ClaimMetadata) -> dispatch::DispatchResult {
+        let max = T::MaxMetadataLength::get() as usize;
+        let fields = [&metadata.content_type, &metadata.uri, &metadata.note];
+        ensure!(
+            fields.iter().all(|field| match field {
+                Some(text) => text.len() <= max,
+                None => true,
+            }),
+            Error::<T, I>::MetadataTooLong
+        );
+        Ok(())
+    }
+
+    fn ensure_valid_reason(reason: &Option<Vec<u8>>) -> dispatch::DispatchResult {
+        ensure!(
+            reason.as_ref().map_or(true, |reason| reason.len()
+                <= T::MaxMetadataLength::get() as usize),
+            Error::<T, I>::ReasonTooLong
+        );
+        Ok(())
+    }
+
+    /// The total amount reserved for the claim on `proof` in `namespace`.
+    fn claim_deposit(
+        namespace: NamespaceId,
+        proof: &T::Proof,
+        claim: &ClaimOf<T, I>,
+    ) -> BalanceOf<T, I> {
+        let document_deposit = Documents::<T, I>::get(namespace, proof)
+            .map_or_else(Zero::zero, |(_, deposit)| deposit);
+        claim
+            .deposit
+            .saturating_add(claim.metadata_deposit)
+            .saturating_add(document_deposit)
+    }
 
-            Self::deposit_event(RawEvent::ClaimRevoked(sender, proof));
+    /// The amount to reserve for attaching `metadata` to a claim.
+    fn metadata_deposit(metadata: &ClaimMetadata) -> BalanceOf<T, I> {
+        if *metadata == ClaimMetadata::default() {
+            return Zero::zero();
         }
+        T::MetadataDepositPerByte::get().saturating_mul((metadata.encoded_size() as u32).into())
     }
 }
@@ -0,0 +1,343 @@
+//! Migrations of the storage of the pallet between `Releases`.
+
+use crate::{
+    Approvals, BalanceOf, CitedBy, Claim, ClaimCount, ClaimMetadata, ClaimsByBlock,
+    ClaimsCreatedTotal, Instance, LegacyProofs, NamespaceId, OwnerClaimCount, PendingRevocations,
+    PendingTransfers, Prices, ProofType, Proofs, References, Releases, RevocationQueue,
+    StorageVersion, SupersededBy, Supersedes, Tombstones, Trait, PUBLIC_NAMESPACE,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+    debug,
+    storage::{unhashed, StoragePrefixedMap},
+    traits::Get,
+    weights::Weight,
+    Blake2_128Concat, IterableStorageDoubleMap, StorageHasher, StorageMap, StorageValue,
+    Twox64Concat,
+};
+use sp_core::hexdisplay::HexDisplay;
+use sp_runtime::traits::Zero;
+use sp_std::vec::Vec;
+
+/// The length of the hash preceding each key hashed with `blake2_128_concat`.
+const BLAKE2_128_LENGTH: usize = 16;
+
+/// Migrate the registry from any earlier release to the latest one.
+pub fn migrate<T: Trait<I>, I: Instance>() -> Weight {
+    migrate_to_v2::<T, I>()
+        .saturating_add(migrate_to_v3::<T, I>())
+        .saturating_add(migrate_to_v4::<T, I>())
+        .saturating_add(migrate_to_v5::<T, I>())
+}
+
+/// Migrate the registry from proofs stored as `Vec<u8>` to proofs stored as `Trait::Proof`.
+///
+/// Claims whose proof has no `Trait::Proof` form, as decided by `ProofType::from_bytes`, are
+/// moved to `LegacyProofs`. Other entries with such proofs are dropped and reported in the log.
+pub fn migrate_to_v2<T: Trait<I>, I: Instance>() -> Weight {
+    if StorageVersion::<I>::get() >= Releases::V2 {
+        return 0;
+    }
+
+    let mut entries = 0;
+    let map = |suffix: &[u8]| {
+        let mut input = suffix;
+        let key = convert_key::<T::Proof>(&mut input)?;
+        Some(key).filter(|_| input.is_empty())
+    };
+    entries += set_aside(
+        &Proofs::<T, I>::final_prefix(),
+        &LegacyProofs::<T, I>::final_prefix(),
+        map,
+    );
+    entries += rekey(&Proofs::<T, I>::final_prefix(), map);
+    entries += rekey(&Supersedes::<T, I>::final_prefix(), map);
+    entries += rekey(&SupersededBy::<T, I>::final_prefix(), map);
+    entries += rekey(&References::<T, I>::final_prefix(), map);
+    entries += rekey(&PendingTransfers::<T, I>::final_prefix(), map);
+    entries += rekey(&Prices::<T, I>::final_prefix(), map);
+    entries += rekey(&Approvals::<T, I>::final_prefix(), map);
+    entries += rekey(&PendingRevocations::<T, I>::final_prefix(), map);
+    entries += rekey(&Tombstones::<T, I>::final_prefix(), map);
+    entries += rekey(&CitedBy::<T, I>::final_prefix(), |suffix| {
+        let mut input = suffix;
+        let mut key = convert_key::<T::Proof>(&mut input)?;
+        key.extend(convert_key::<T::Proof>(&mut input)?);
+        Some(key).filter(|_| input.is_empty())
+    });
+
+    // The values of these maps are proofs as well.
+    let convert = |legacy: Vec<u8>| T::Proof::from_bytes(&legacy);
+    let convert_all = |legacy: Vec<Vec<u8>>| {
+        Some(
+            legacy
+                .iter()
+                .filter_map(|proof| T::Proof::from_bytes(proof))
+                .collect::<Vec<_>>(),
+        )
+    };
+    translate(&Supersedes::<T, I>::final_prefix(), convert);
+    translate(&SupersededBy::<T, I>::final_prefix(), convert);
+    translate(&References::<T, I>::final_prefix(), convert_all);
+    translate(&RevocationQueue::<T, I>::final_prefix(), convert_all);
+
+    StorageVersion::<I>::put(Releases::V2);
+
+    T::DbWeight::get().reads_writes(2 * entries + 1, 2 * entries + 1)
+}
+
+/// A claim as stored by the first release of the pallet, before deposits were reserved.
+type ClaimV1<AccountId, BlockNumber> = (AccountId, BlockNumber);
+
+/// A claim as stored before `Releases::V3`, once deposits and metadata were recorded.
+#[derive(Encode, Decode)]
+struct ClaimV2<AccountId, BlockNumber, Balance> {
+    owner: AccountId,
+    block_number: BlockNumber,
+    deposit: Balance,
+    metadata: ClaimMetadata,
+    metadata_deposit: Balance,
+}
+
+impl<AccountId: Decode, BlockNumber: Decode, Balance: Decode + Zero>
+    ClaimV2<AccountId, BlockNumber, Balance>
+{
+    /// Decode a claim stored before `Releases::V3` in either layout. Claims stored as
+    /// `ClaimV1` reserved nothing and are given no deposit and no metadata.
+    fn decode_any(raw: &[u8]) -> Option<Self> {
+        if let Some(claim) = decode_exact(raw) {
+            return Some(claim);
+        }
+        let (owner, block_number) = decode_exact::<ClaimV1<AccountId, BlockNumber>>(raw)?;
+        Some(ClaimV2 {
+            owner,
+            block_number,
+            deposit: Zero::zero(),
+            metadata: ClaimMetadata::default(),
+            metadata_deposit: Zero::zero(),
+        })
+    }
+}
+
+/// Migrate the registry to claims recording the hash function of their proof.
+///
+/// Existing claims, including those in `LegacyProofs`, record no hash function. Claims still in
+/// the `ClaimV1` layout are converted as well.
+pub fn migrate_to_v3<T: Trait<I>, I: Instance>() -> Weight {
+    if StorageVersion::<I>::get() >= Releases::V3 {
+        return 0;
+    }
+
+    let upgrade = |raw: &[u8]| {
+        ClaimV2::<T::AccountId, T::BlockNumber, BalanceOf<T, I>>::decode_any(raw).map(|legacy| {
+            Claim {
+                owner: legacy.owner,
+                block_number: legacy.block_number,
+                deposit: legacy.deposit,
+                metadata: legacy.metadata,
+                metadata_deposit: legacy.metadata_deposit,
+                algorithm: None,
+            }
+        })
+    };
+    let entries = translate_raw(&Proofs::<T, I>::final_prefix(), upgrade)
+        + translate_raw(&LegacyProofs::<T, I>::final_prefix(), upgrade);
+
+    StorageVersion::<I>::put(Releases::V3);
+
+    T::DbWeight::get().reads_writes(entries + 1, entries + 1)
+}
+
+/// Migrate the registry to proofs claimed within namespaces.
+///
+/// Existing claims, and everything attached to them, are moved to `PUBLIC_NAMESPACE`.
+pub fn migrate_to_v4<T: Trait<I>, I: Instance>() -> Weight {
+    if StorageVersion::<I>::get() >= Releases::V4 {
+        return 0;
+    }
+
+    let namespace = Twox64Concat::hash(&PUBLIC_NAMESPACE.encode());
+    let map = |suffix: &[u8]| Some([&namespace[..], suffix].concat());
+    let mut entries = 0;
+    entries += rekey(&Proofs::<T, I>::final_prefix(), map);
+    entries += rekey(&Supersedes::<T, I>::final_prefix(), map);
+    entries += rekey(&SupersededBy::<T, I>::final_prefix(), map);
+    entries += rekey(&References::<T, I>::final_prefix(), map);
+    entries += rekey(&PendingTransfers::<T, I>::final_prefix(), map);
+    entries += rekey(&Prices::<T, I>::final_prefix(), map);
+    entries += rekey(&Approvals::<T, I>::final_prefix(), map);
+    entries += rekey(&PendingRevocations::<T, I>::final_prefix(), map);
+    entries += rekey(&Tombstones::<T, I>::final_prefix(), map);
+    entries += rekey(&CitedBy::<T, I>::final_prefix(), |suffix| {
+        if suffix.len() < BLAKE2_128_LENGTH {
+            return None;
+        }
+        let mut input = &suffix[BLAKE2_128_LENGTH..];
+        let cited = T::Proof::decode(&mut input).ok()?;
+        let key = Blake2_128Concat::hash(&(PUBLIC_NAMESPACE, cited).encode());
+        Some([&key[..], input].concat())
+    });
+
+    translate(
+        &RevocationQueue::<T, I>::final_prefix(),
+        |legacy: Vec<T::Proof>| {
+            Some(
+                legacy
+                    .into_iter()
+                    .map(|proof| (PUBLIC_NAMESPACE, proof))
+                    .collect::<Vec<(NamespaceId, T::Proof)>>(),
+            )
+        },
+    );
+
+    StorageVersion::<I>::put(Releases::V4);
+
+    T::DbWeight::get().reads_writes(2 * entries + 1, 2 * entries + 1)
+}
+
+/// Migrate the registry to claims indexed by the block they were made in.
+///
+/// Existing claims are appended to `ClaimsByBlock` in the order of `Proofs`, regardless of
+/// `MaxClaimsPerBlock`.
+///
+/// The claim counters are recounted from `Proofs` along the way, as earlier releases only
+/// counted the claims created after the counters were introduced.
+pub fn migrate_to_v5<T: Trait<I>, I: Instance>() -> Weight {
+    if StorageVersion::<I>::get() >= Releases::V5 {
+        return 0;
+    }
+
+    OwnerClaimCount::<T, I>::remove_all();
+    let mut entries = 0;
+    for (namespace, proof, claim) in Proofs::<T, I>::iter() {
+        ClaimsByBlock::<T, I>::append(claim.block_number, (namespace, &proof));
+        OwnerClaimCount::<T, I>::mutate(&claim.owner, |count| *count = count.saturating_add(1));
+        entries += 1;
+    }
+    ClaimCount::<I>::put(entries);
+    ClaimsCreatedTotal::<I>::mutate(|total| *total = (*total).max(entries));
+
+    StorageVersion::<I>::put(Releases::V5);
+
+    T::DbWeight::get().reads_writes(3 * entries + 2, 2 * entries + 4)
+}
+
+/// Replace the value of every entry stored under `prefix` by the value returned by
+/// `translate`. Entries that cannot be decoded, or for which `translate` returns `None`, are
+/// removed and reported in the log.
+///
+/// Unlike `StoragePrefixedMap::translate_values`, the types involved need not be those of the
+/// current release.
+///
+/// Returns the number of entries found.
+fn translate<O: Decode, V: Encode>(
+    prefix: &[u8],
+    mut translate: impl FnMut(O) -> Option<V>,
+) -> u64 {
+    translate_raw(prefix, |raw| translate(O::decode(&mut &raw[..]).ok()?))
+}
+
+/// Like `translate`, but handing the raw value of each entry to `translate`, for values stored
+/// in more than one layout.
+fn translate_raw<V: Encode>(prefix: &[u8], mut translate: impl FnMut(&[u8]) -> Option<V>) -> u64 {
+    let mut entries = 0;
+    let mut previous = prefix.to_vec();
+    while let Some(key) = sp_io::storage::next_key(&previous) {
+        if !key.starts_with(prefix) {
+            break;
+        }
+        match unhashed::get_raw(&key).and_then(|raw| translate(&raw)) {
+            Some(value) => unhashed::put(&key, &value),
+            None => {
+                debug::warn!(
+                    "Removing storage entry {} whose value could not be migrated",
+                    HexDisplay::from(&key)
+                );
+                unhashed::kill(&key);
+            }
+        }
+        entries += 1;
+        previous = key;
+    }
+    entries
+}
+
+/// Replace the key of every entry stored under `prefix`, given the part of the key following
+/// `prefix`, by the key returned by `rekey`. Entries for which `rekey` returns `None` are removed
+/// and reported in the log.
+///
+/// Returns the number of entries found.
+fn rekey(prefix: &[u8], rekey: impl Fn(&[u8]) -> Option<Vec<u8>>) -> u64 {
+    let mut entries = Vec::new();
+    let mut previous = prefix.to_vec();
+    while let Some(key) = sp_io::storage::next_key(&previous) {
+        if !key.starts_with(prefix) {
+            break;
+        }
+        if let Some(value) = unhashed::get_raw(&key) {
+            entries.push((key.clone(), value));
+        }
+        previous = key;
+    }
+
+    // Remove every entry before writing any, as old and new keys may coincide.
+    for (key, _) in &entries {
+        unhashed::kill(key);
+    }
+    for (key, value) in &entries {
+        match rekey(&key[prefix.len()..]) {
+            Some(suffix) => unhashed::put_raw(&[prefix, &suffix[..]].concat(), value),
+            None => debug::warn!(
+                "Removing storage entry {} whose key could not be migrated",
+                HexDisplay::from(key)
+            ),
+        }
+    }
+
+    entries.len() as u64
+}
+
+/// Move every entry stored under `from` whose key `rekey` cannot convert, given the part of the
+/// key following `from`, to the same part of the key under `to`.
+///
+/// Returns the number of entries moved.
+fn set_aside(from: &[u8], to: &[u8], rekey: impl Fn(&[u8]) -> Option<Vec<u8>>) -> u64 {
+    let mut moved = Vec::new();
+    let mut previous = from.to_vec();
+    while let Some(key) = sp_io::storage::next_key(&previous) {
+        if !key.starts_with(from) {
+            break;
+        }
+        if rekey(&key[from.len()..]).is_none() {
+            moved.push(key.clone());
+        }
+        previous = key;
+    }
+
+    for key in &moved {
+        if let Some(value) = unhashed::get_raw(key) {
+            unhashed::put_raw(&[to, &key[from.len()..]].concat(), &value);
+        }
+        unhashed::kill(key);
+    }
+
+    moved.len() as u64
+}
+
+/// Read a `Vec<u8>` proof hashed with `blake2_128_concat` from the front of `input`, and return
+/// the same proof as `P` hashed with `blake2_128_concat`.
+fn convert_key<P: ProofType>(input: &mut &[u8]) -> Option<Vec<u8>> {
+    if input.len() < BLAKE2_128_LENGTH {
+        return None;
+    }
+    *input = &input[BLAKE2_128_LENGTH..];
+    let legacy = Vec::<u8>::decode(input).ok()?;
+    let proof = P::from_bytes(&legacy)?;
+    Some(Blake2_128Concat::hash(&proof.encode()))
+}
+
+/// `O` decoded from `raw`, if `raw` holds nothing else.
+fn decode_exact<O: Decode>(mut raw: &[u8]) -> Option<O> {
+    let value = O::decode(&mut raw).ok()?;
+    Some(value).filter(|_| raw.is_empty())
+}
@@ -1,12 +1,18 @@
-use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use crate::{
+    validation::{Exact32Bytes, Multihash},
+    HashAlgorithm, Instance1, Instance2, Module, OnClaimCreated, OnClaimRevoked,
+    OnClaimTransferred, ReclaimPolicy, Trait,
+};
+use codec::Codec;
+use frame_support::{impl_outer_origin, parameter_types, traits::OnInitialize, weights::Weight};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-    testing::Header,
+    testing::{Header, TestSignature, TestXt, UintAuthorityId},
     traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
+    ModuleId, Perbill,
 };
+use std::cell::RefCell;
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -45,22 +51,256 @@ impl system::Trait for Test {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type PalletInfo = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
 
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+pub type Extrinsic<C> = TestXt<C, ()>;
+
+impl system::offchain::SigningTypes for Test {
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+}
+
+impl<LocalCall: Codec + Send + Sync> system::offchain::SendTransactionTypes<LocalCall> for Test {
+    type OverarchingCall = LocalCall;
+    type Extrinsic = Extrinsic<LocalCall>;
+}
+
+impl<LocalCall: Codec + Send + Sync> system::offchain::CreateSignedTransaction<LocalCall> for Test {
+    fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: LocalCall,
+        _public: UintAuthorityId,
+        _account: u64,
+        nonce: u64,
+    ) -> Option<(LocalCall, (u64, ()))> {
+        Some((call, (nonce, ())))
+    }
+}
+
+// Signs the reports of the offchain worker with the keys set by `UintAuthorityId::set_all_keys`.
+pub struct TestVerifierId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestVerifierId {
+    type RuntimeAppPublic = UintAuthorityId;
+    type GenericSignature = TestSignature;
+    type GenericPublic = UintAuthorityId;
+}
+
+thread_local! {
+    pub static CREATED: RefCell<Vec<(u64, Vec<u8>)>> = RefCell::new(vec![]);
+    pub static TRANSFERRED: RefCell<Vec<(u64, u64, Vec<u8>)>> = RefCell::new(vec![]);
+    pub static REVOKED: RefCell<Vec<(u64, Vec<u8>)>> = RefCell::new(vec![]);
+}
+
+// Records the claims created, transferred and revoked in the default registry.
+pub struct RecordClaims;
+
+impl OnClaimCreated<u64> for RecordClaims {
+    fn on_claim_created(owner: &u64, proof: &[u8]) {
+        CREATED.with(|created| created.borrow_mut().push((*owner, proof.to_vec())));
+    }
+}
+
+impl OnClaimTransferred<u64> for RecordClaims {
+    fn on_claim_transferred(from: &u64, to: &u64, proof: &[u8]) {
+        TRANSFERRED.with(|transferred| transferred.borrow_mut().push((*from, *to, proof.to_vec())));
+    }
+}
+
+impl OnClaimRevoked<u64> for RecordClaims {
+    fn on_claim_revoked(owner: &u64, proof: &[u8]) {
+        REVOKED.with(|revoked| revoked.borrow_mut().push((*owner, proof.to_vec())));
+    }
+}
+
+parameter_types! {
+    pub const ClaimDeposit: u64 = 10;
+    pub const ProofHashing: HashAlgorithm = HashAlgorithm::Blake2_256;
+    pub const MinProofLength: u32 = 1;
+    pub const MaxProofLength: u32 = 8;
+    pub const MaxContentLength: u32 = 64;
+    pub const MaxMetadataLength: u32 = 16;
+    pub const MetadataDepositPerByte: u64 = 1;
+    pub const MaxReferences: u32 = 2;
+    pub const MaxClaimsPerBlock: u32 = 16;
+    pub const MaxInlineDocument: u32 = 16;
+    pub const DocumentDepositPerByte: u64 = 1;
+    pub const MaxIndexedDocument: u32 = 128;
+    pub const NamespaceDeposit: u64 = 20;
+    pub const PoeModuleId: ModuleId = ModuleId(*b"py/poe00");
+    pub const OfferExpiry: u64 = 10;
+    pub const RevokeDelay: u64 = 0;
+    pub const Reclaim: ReclaimPolicy = ReclaimPolicy::Allow;
+    pub const ClaimPeriod: u64 = 0;
+    pub const RenewalFeePerBlock: u64 = 1;
+    pub const MaxVerifications: u32 = 2;
+}
+
 impl Trait for Test {
     type Event = ();
+    type Currency = Balances;
+    type Proof = Vec<u8>;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = ClaimDeposit;
+    type MinProofLength = MinProofLength;
+    type MaxProofLength = MaxProofLength;
+    type ProofValidator = ();
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = MaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = PoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = RevokeDelay;
+    type ReclaimPolicy = Reclaim;
+    type ClaimPeriod = ClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = RecordClaims;
+    type OnClaimTransferred = RecordClaims;
+    type OnClaimRevoked = RecordClaims;
+    type PauseOrigin = system::EnsureRoot<u64>;
+    type VerifierOrigin = system::EnsureSignedBy<PoeModule, u64>;
+    type VerifierManagerOrigin = system::EnsureRoot<u64>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = TestVerifierId;
+}
+
+parameter_types! {
+    pub const OtherClaimDeposit: u64 = 50;
+    pub const OtherProofLength: u32 = 4;
+    pub const OtherPoeModuleId: ModuleId = ModuleId(*b"py/poe01");
+    pub const OtherRevokeDelay: u64 = 3;
+    pub const OtherReclaim: ReclaimPolicy = ReclaimPolicy::OwnerOnly;
+    pub const OtherClaimPeriod: u64 = 20;
+}
+
+impl Trait<Instance1> for Test {
+    type Event = ();
+    type Currency = Balances;
+    type Proof = Vec<u8>;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = OtherClaimDeposit;
+    type MinProofLength = OtherProofLength;
+    type MaxProofLength = OtherProofLength;
+    type ProofValidator = Multihash;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = MaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = OtherPoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = OtherRevokeDelay;
+    type ReclaimPolicy = OtherReclaim;
+    type ClaimPeriod = OtherClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = ();
+    type OnClaimTransferred = ();
+    type OnClaimRevoked = ();
+    type PauseOrigin = system::EnsureRoot<u64>;
+    type VerifierOrigin = system::EnsureSignedBy<OtherPoe, u64>;
+    type VerifierManagerOrigin = system::EnsureRoot<u64>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = TestVerifierId;
 }
 
-pub type TemplateModule = Module<Test>;
+parameter_types! {
+    pub const HashProofLength: u32 = 32;
+    pub const HashMaxClaimsPerBlock: u32 = 2;
+    pub const HashPoeModuleId: ModuleId = ModuleId(*b"py/poe02");
+}
+
+impl Trait<Instance2> for Test {
+    type Event = ();
+    type Currency = Balances;
+    type Proof = H256;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = ClaimDeposit;
+    type MinProofLength = HashProofLength;
+    type MaxProofLength = HashProofLength;
+    type ProofValidator = Exact32Bytes;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = HashMaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = HashPoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = RevokeDelay;
+    type ReclaimPolicy = Reclaim;
+    type ClaimPeriod = ClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = ();
+    type OnClaimTransferred = ();
+    type OnClaimRevoked = ();
+    type PauseOrigin = system::EnsureRoot<u64>;
+    type VerifierOrigin = system::EnsureSignedBy<HashPoe, u64>;
+    type VerifierManagerOrigin = system::EnsureRoot<u64>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = TestVerifierId;
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type PoeModule = Module<Test>;
+pub type OtherPoe = Module<Test, Instance1>;
+pub type HashPoe = Module<Test, Instance2>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut t = system::GenesisConfig::default()
         .build_storage::<Test>()
-        .unwrap()
-        .into()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 100), (2, 100), (3, 5)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+// Run the pallets' block initialization up to block `n`.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        PoeModule::on_initialize(System::block_number());
+        OtherPoe::on_initialize(System::block_number());
+        HashPoe::on_initialize(System::block_number());
+    }
 }
@@ -0,0 +1,150 @@
+//! Offchain verification of the documents behind new claims.
+//!
+//! Nodes configure the gateway documents are fetched from by setting `GATEWAY_KEY` in their
+//! persistent offchain storage, e.g. with the `offchain_localStorageSet` RPC. The URI of each
+//! claim must be a path relative to the gateway, and documents longer than `MaxContentLength`
+//! are not verified. Without a gateway, or without a `KEY_TYPE` key in the keystore, the worker
+//! does nothing.
+
+use crate::{Call, ClaimOf, Instance, NamespaceId, ProofType, Trait};
+use frame_support::{debug, traits::Get};
+use frame_system::offchain::{SendSignedTransaction, Signer};
+use sp_core::{crypto::KeyTypeId, offchain::StorageKind};
+use sp_runtime::offchain::{http, Duration};
+use sp_std::vec::Vec;
+
+/// The key type of the accounts reporting verifications.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"poe!");
+
+/// The key of the persistent offchain storage holding the gateway documents are fetched from.
+pub const GATEWAY_KEY: &[u8] = b"poe::gateway";
+
+/// How long to wait for a document, in milliseconds.
+const FETCH_TIMEOUT: u64 = 5_000;
+
+/// The crypto of the accounts reporting verifications.
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Identifies the verifier keys of the keystore to `frame_system::offchain::Signer`.
+    pub struct VerifierId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for VerifierId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
+
+/// Fetch the documents of the claims with a URI created in the current block, and report
+/// whether they match their proofs.
+pub fn verify_new_claims<T: Trait<I>, I: Instance>() {
+    let claims = crate::UnverifiedClaims::<T, I>::get();
+    if claims.is_empty() {
+        return;
+    }
+    let gateway = match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, GATEWAY_KEY) {
+        Some(gateway) => gateway,
+        None => return,
+    };
+    let signer = Signer::<T, T::AuthorityId>::all_accounts();
+    if !signer.can_sign() {
+        debug::warn!("No key to report verifications of claims with");
+        return;
+    }
+
+    for (namespace, proof) in claims {
+        let claim = match crate::Proofs::<T, I>::get(namespace, &proof) {
+            Some(claim) => claim,
+            None => continue,
+        };
+        let ok = match verify::<T, I>(&gateway, &proof, &claim) {
+            Some(ok) => ok,
+            None => continue,
+        };
+        report::<T, I>(&signer, namespace, proof, ok);
+    }
+}
+
+/// Whether the document behind `claim` hashes to `proof`, or `None` if it cannot be fetched.
+///
+/// The document is hashed with the algorithm recorded with the claim, or else the one named by
+/// the proof itself. Claims whose algorithm is unknown are not verified.
+fn verify<T: Trait<I>, I: Instance>(
+    gateway: &[u8],
+    proof: &T::Proof,
+    claim: &ClaimOf<T, I>,
+) -> Option<bool> {
+    let uri = claim.metadata.uri.as_ref()?;
+    let algorithm = claim.algorithm.or_else(|| proof.algorithm())?;
+    let uri = sp_std::str::from_utf8(uri)
+        .ok()
+        .filter(|uri| is_relative_path(uri))?;
+    let gateway = sp_std::str::from_utf8(gateway).ok()?;
+    let separator = if gateway.ends_with('/') { "" } else { "/" };
+    let url = [gateway, separator, uri].concat();
+    let document = match fetch(&url, T::MaxContentLength::get() as usize) {
+        Ok(document) => document,
+        Err(err) => {
+            debug::warn!("Failed to fetch {}: {:?}", url, err);
+            return None;
+        }
+    };
+    Some(T::Proof::from_digest(algorithm, &algorithm.hash(&document)).as_ref() == Some(proof))
+}
+
+/// Whether `uri` is a path relative to the gateway, which cannot lead the request to another
+/// host or out of the gateway.
+fn is_relative_path(uri: &str) -> bool {
+    let first_segment = uri.split('/').next().unwrap_or_default();
+    !uri.is_empty()
+        && !uri.starts_with('/')
+        && !uri.contains("//")
+        && !uri.contains("..")
+        && !first_segment.contains(':')
+        && !uri
+            .chars()
+            .any(|c| c == '@' || c == '\\' || c == '%' || c.is_whitespace() || c.is_control())
+}
+
+/// The document at `url`, unless it is longer than `limit` bytes.
+fn fetch(url: &str, limit: usize) -> Result<Vec<u8>, http::Error> {
+    let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT));
+    let pending = http::Request::get(url)
+        .deadline(deadline)
+        .send()
+        .map_err(|_| http::Error::IoError)?;
+    let response = pending
+        .try_wait(deadline)
+        .map_err(|_| http::Error::DeadlineReached)??;
+    if response.code != 200 {
+        return Err(http::Error::Unknown);
+    }
+    let document: Vec<u8> = response.body().take(limit + 1).collect();
+    if document.len() > limit {
+        debug::warn!("Document at {} is longer than {} bytes", url, limit);
+        return Err(http::Error::Unknown);
+    }
+    Ok(document)
+}
+
+fn report<T: Trait<I>, I: Instance>(
+    signer: &Signer<T, T::AuthorityId, frame_system::offchain::ForAll>,
+    namespace: NamespaceId,
+    proof: T::Proof,
+    ok: bool,
+) {
+    let results =
+        signer.send_signed_transaction(|_| Call::report_verification(namespace, proof.clone(), ok));
+    for (account, result) in results {
+        if result.is_err() {
+            debug::warn!("Failed to report verification from {:?}", account.id);
+        }
+    }
+}
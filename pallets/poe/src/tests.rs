@@ -1,23 +1,2419 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+    migration,
+    mock::*,
+    offchain,
+    validation::{CidV1, Exact32Bytes, Multihash},
+    CitedBy, Claim, ClaimCount, ClaimMetadata, ClaimStats, ClaimsByBlock, ClaimsCreatedTotal,
+    ClaimsPage, DefaultInstance, Error, HashAlgorithm, Instance1, Instance2, NextNamespaceId,
+    NextOrgId, OrgRole, OwnerClaimCount, PendingRevocations, PendingTransfer, ProofType,
+    ProofValidator, Proofs, References, Releases, RevocationQueue, StorageVersion, SupersededBy,
+    Supersedes, Tombstone, WEIGHT_PER_CONTENT_BYTE,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+    assert_noop, assert_ok,
+    storage::{unhashed, StoragePrefixedMap},
+    traits::{Get, OffchainWorker},
+    weights::GetDispatchInfo,
+    Blake2_128Concat, IterableStorageDoubleMap, StorageHasher, StorageMap, StorageValue,
+    Twox64Concat,
+};
+use sp_core::{
+    offchain::{
+        testing::{PendingRequest, TestOffchainExt, TestTransactionPoolExt},
+        OffchainExt, StorageKind, TransactionPoolExt,
+    },
+    H256,
+};
+use sp_runtime::{
+    testing::UintAuthorityId,
+    traits::{BlakeTwo256, Hash},
+};
 
 #[test]
-fn it_works_for_default_value() {
+fn create_claim_works() {
     new_test_ext().execute_with(|| {
-        // Dispatch a signed extrinsic.
-        assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-        // Read pallet storage and assert an expected result.
-        assert_eq!(TemplateModule::something(), Some(42));
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_eq!(
+            PoeModule::proofs(0, vec![0, 1]),
+            Some(Claim {
+                owner: 1,
+                block_number: 1,
+                deposit: 10,
+                metadata: ClaimMetadata::default(),
+                metadata_deposit: 0,
+                algorithm: None,
+            })
+        );
+        assert_eq!(Balances::reserved_balance(1), 10);
+    });
+}
+
+#[test]
+fn create_claim_fails_when_already_claimed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), 0, vec![0, 1], None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+    });
+}
+
+#[test]
+fn create_claim_checks_proof_length() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 0, vec![], None),
+            Error::<Test>::ProofTooShort
+        );
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 0, vec![0; 9], None),
+            Error::<Test>::ProofTooLong
+        );
+    });
+}
+
+#[test]
+fn create_claim_requires_deposit() {
+    new_test_ext().execute_with(|| {
+        assert!(PoeModule::create_claim(Origin::signed(3), 0, vec![0, 1], None).is_err());
+        assert_eq!(PoeModule::proofs(0, vec![0, 1]), None);
+    });
+}
+
+fn metadata() -> ClaimMetadata {
+    ClaimMetadata {
+        content_type: Some(b"text/plain".to_vec()),
+        size: Some(42),
+        uri: None,
+        note: None,
+    }
+}
+
+#[test]
+fn create_claim_with_metadata_reserves_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            Some(metadata())
+        ));
+
+        let claim = PoeModule::proofs(0, vec![0, 1]).unwrap();
+        let metadata_deposit = metadata().encoded_size() as u64;
+        assert_eq!(claim.metadata, metadata());
+        assert_eq!(claim.metadata_deposit, metadata_deposit);
+        assert_eq!(Balances::reserved_balance(1), 10 + metadata_deposit);
+    });
+}
+
+#[test]
+fn create_claim_rejects_long_metadata() {
+    new_test_ext().execute_with(|| {
+        let metadata = ClaimMetadata {
+            note: Some(vec![b'x'; 17]),
+            ..Default::default()
+        };
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 0, vec![0, 1], Some(metadata)),
+            Error::<Test>::MetadataTooLong
+        );
+    });
+}
+
+#[test]
+fn set_metadata_adjusts_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_ok!(PoeModule::set_metadata(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            metadata()
+        ));
+        let metadata_deposit = metadata().encoded_size() as u64;
+        assert_eq!(
+            PoeModule::proofs(0, vec![0, 1]).unwrap().metadata,
+            metadata()
+        );
+        assert_eq!(Balances::reserved_balance(1), 10 + metadata_deposit);
+
+        assert_ok!(PoeModule::set_metadata(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            Default::default()
+        ));
+        assert_eq!(
+            PoeModule::proofs(0, vec![0, 1]).unwrap().metadata_deposit,
+            0
+        );
+        assert_eq!(Balances::reserved_balance(1), 10);
+    });
+}
+
+#[test]
+fn set_metadata_fails_for_other_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_noop!(
+            PoeModule::set_metadata(Origin::signed(2), 0, vec![0, 1], metadata()),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_releases_metadata_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            Some(metadata())
+        ));
+        assert_ok!(PoeModule::revoke_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 100);
+    });
+}
+
+#[test]
+fn revoke_claim_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+        assert_ok!(PoeModule::revoke_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_eq!(PoeModule::proofs(0, vec![0, 1]), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 100);
+    });
+}
+
+#[test]
+fn revoke_claim_fails_for_unknown_proof() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(1), 0, vec![0, 1], None),
+            Error::<Test>::NoSuchProof
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_fails_for_other_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(2), 0, vec![0, 1], None),
+            Error::<Test>::NotProofOwner
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_leaves_tombstone() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+        System::set_block_number(5);
+
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(1), 0, vec![0, 1], Some(vec![b'x'; 17])),
+            Error::<Test>::ReasonTooLong
+        );
+        assert_ok!(PoeModule::revoke_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            Some(b"leaked".to_vec())
+        ));
+        assert_eq!(
+            PoeModule::tombstone(0, vec![0, 1]),
+            Some(Tombstone {
+                owner: 1,
+                claimed_at: 1,
+                revoked_at: 5,
+                reason: Some(b"leaked".to_vec()),
+            })
+        );
+
+        // The registry allows anyone to claim the proof again.
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(2),
+            0,
+            vec![0, 1],
+            None
+        ));
+        assert_eq!(PoeModule::tombstone(0, vec![0, 1]).unwrap().owner, 1);
+    });
+}
+
+#[test]
+fn delayed_revocation_records_reason() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_noop!(
+            OtherPoe::request_revoke(Origin::signed(1), 0, proof.clone(), Some(vec![b'x'; 17])),
+            Error::<Test, Instance1>::ReasonTooLong
+        );
+
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            Some(b"typo".to_vec())
+        ));
+        assert_ok!(OtherPoe::cancel_revoke(Origin::signed(1), 0, proof.clone()));
+        assert_eq!(OtherPoe::revocation_reason(0, &proof), None);
+
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            Some(b"leaked".to_vec())
+        ));
+        assert_eq!(
+            OtherPoe::revocation_reason(0, &proof),
+            Some(b"leaked".to_vec())
+        );
+        run_to_block(4);
+        assert_eq!(
+            OtherPoe::tombstone(0, &proof).unwrap().reason,
+            Some(b"leaked".to_vec())
+        );
+        assert_eq!(OtherPoe::revocation_reason(0, &proof), None);
+    });
+}
+
+#[test]
+fn tombstoned_proof_can_only_be_reclaimed_by_owner() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        run_to_block(4);
+        assert_eq!(OtherPoe::tombstone(0, &proof).unwrap().reason, None);
+
+        assert_noop!(
+            OtherPoe::create_claim(Origin::signed(2), 0, proof.clone(), None),
+            Error::<Test, Instance1>::ProofTombstoned
+        );
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+    });
+}
+
+#[test]
+fn handlers_are_called() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            vec![2]
+        ));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![2], 2));
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), 0, vec![2]));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), 0, vec![2], None));
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 0, vec![1], None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+
+        CREATED.with(|created| assert_eq!(*created.borrow(), vec![(1, vec![1]), (1, vec![2])]));
+        TRANSFERRED.with(|transferred| assert_eq!(*transferred.borrow(), vec![(1, 2, vec![2])]));
+        REVOKED.with(|revoked| assert_eq!(*revoked.borrow(), vec![(2, vec![2])]));
+    });
+}
+
+#[test]
+fn paused_registry_rejects_calls() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_noop!(
+            PoeModule::set_paused(Origin::signed(1), true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(PoeModule::set_paused(Origin::root(), true));
+        assert!(PoeModule::paused());
+        assert!(!PoeModule::is_call_allowed(&crate::Call::create_org()));
+        assert!(PoeModule::is_call_allowed(&crate::Call::set_paused(false)));
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 0, vec![2], None),
+            Error::<Test>::PalletPaused
+        );
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None),
+            Error::<Test>::PalletPaused
+        );
+        assert_noop!(
+            PoeModule::create_org(Origin::signed(1)),
+            Error::<Test>::PalletPaused
+        );
+        // Other registries are not affected.
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 2, 2, 3],
+            None
+        ));
+
+        assert_ok!(PoeModule::set_paused(Origin::root(), false));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+    });
+}
+
+#[test]
+fn counters_follow_claims() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![2], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), 0, vec![3], None));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::approve(Origin::signed(1), 0, vec![2], Some(2)));
+        assert_ok!(PoeModule::transfer_from(Origin::signed(2), 0, vec![2], 2));
+
+        assert_eq!(
+            PoeModule::stats(),
+            ClaimStats {
+                count: 2,
+                created: 3,
+                revoked: 1,
+                expired: 0,
+            }
+        );
+        assert_eq!(PoeModule::owner_claim_count(1), 0);
+        assert_eq!(PoeModule::owner_claim_count(2), 2);
+        assert_eq!(OtherPoe::stats(), ClaimStats::default());
+    });
+}
+
+#[test]
+fn store_document_keeps_content_until_revoked() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            HashPoe::store_document(Origin::signed(1), 0, vec![7; 17]),
+            Error::<Test, Instance2>::DocumentTooLong
+        );
+        assert_ok!(HashPoe::store_document(
+            Origin::signed(1),
+            0,
+            b"I agree".to_vec()
+        ));
+
+        let proof = BlakeTwo256::hash(b"I agree");
+        assert_eq!(HashPoe::proofs(0, proof).unwrap().owner, 1);
+        assert_eq!(HashPoe::document(0, proof), Some(b"I agree".to_vec()));
+        assert_eq!(Balances::reserved_balance(1), 10 + 7);
+        assert_noop!(
+            HashPoe::store_document(Origin::signed(2), 0, b"I agree".to_vec()),
+            Error::<Test, Instance2>::ProofAlreadyClaimed
+        );
+
+        assert_ok!(HashPoe::offer_claim(Origin::signed(1), 0, proof, 2));
+        assert_ok!(HashPoe::accept_claim(Origin::signed(2), 0, proof));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 10 + 7);
+
+        assert_ok!(HashPoe::revoke_claim(Origin::signed(2), 0, proof, None));
+        assert_eq!(HashPoe::document(0, proof), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+/// The key a `Vec<u8>` proof was stored under in the map at `prefix`.
+fn legacy_key(prefix: [u8; 32], proof: &[u8]) -> Vec<u8> {
+    [&prefix[..], &Blake2_128Concat::hash(&proof.encode())].concat()
+}
+
+/// A claim in the layout stored by the first release, made in block 1.
+fn first_release_claim(owner: u64) -> (u64, u64) {
+    (owner, 1)
+}
+
+/// A claim in the layout stored before `Releases::V3`, once deposits were reserved.
+fn legacy_claim(owner: u64) -> (u64, u64, u64, ClaimMetadata, u64) {
+    (owner, 1, 10, ClaimMetadata::default(), 0)
+}
+
+#[test]
+fn migrate_converts_proofs() {
+    new_test_ext().execute_with(|| {
+        let first = H256::repeat_byte(1);
+        let second = H256::repeat_byte(2);
+        let proofs = Proofs::<Test, Instance2>::final_prefix();
+        unhashed::put(
+            &legacy_key(proofs, first.as_bytes()),
+            &first_release_claim(1),
+        );
+        unhashed::put(
+            &legacy_key(proofs, second.as_bytes()),
+            &first_release_claim(1),
+        );
+        unhashed::put(&legacy_key(proofs, &[3; 31]), &first_release_claim(1));
+        unhashed::put(
+            &legacy_key(
+                Supersedes::<Test, Instance2>::final_prefix(),
+                second.as_bytes(),
+            ),
+            &first.as_bytes().to_vec(),
+        );
+        unhashed::put(
+            &legacy_key(
+                SupersededBy::<Test, Instance2>::final_prefix(),
+                first.as_bytes(),
+            ),
+            &second.as_bytes().to_vec(),
+        );
+
+        assert_eq!(StorageVersion::<Instance2>::get(), Releases::V1);
+        migration::migrate::<Test, Instance2>();
+        assert_eq!(StorageVersion::<Instance2>::get(), Releases::V5);
+
+        let claim = Claim {
+            owner: 1,
+            block_number: 1,
+            deposit: 0,
+            metadata: ClaimMetadata::default(),
+            metadata_deposit: 0,
+            algorithm: None,
+        };
+        assert_eq!(HashPoe::proofs(0, first), Some(claim.clone()));
+        assert_eq!(HashPoe::proofs(0, second), Some(claim));
+        assert_eq!(HashPoe::version_history(0, first), vec![first, second]);
+        // The claim on a proof that is not a digest is kept aside.
+        assert_eq!(Proofs::<Test, Instance2>::iter().count(), 2);
+        assert_eq!(HashPoe::legacy_proof(vec![3; 31]), Some(claim));
+    });
+}
+
+#[test]
+fn migrate_keeps_byte_proofs() {
+    new_test_ext().execute_with(|| {
+        let proofs = Proofs::<Test, DefaultInstance>::final_prefix();
+        for proof in &[[1], [2], [3]] {
+            unhashed::put(&legacy_key(proofs, proof), &first_release_claim(1));
+        }
+        unhashed::put(
+            &legacy_key(Supersedes::<Test, DefaultInstance>::final_prefix(), &[2]),
+            &vec![1u8],
+        );
+        unhashed::put(
+            &legacy_key(SupersededBy::<Test, DefaultInstance>::final_prefix(), &[1]),
+            &vec![2u8],
+        );
+        unhashed::put(
+            &legacy_key(References::<Test, DefaultInstance>::final_prefix(), &[3]),
+            &vec![vec![2u8]],
+        );
+        let cited_by = legacy_key(CitedBy::<Test, DefaultInstance>::final_prefix(), &[2]);
+        unhashed::put(
+            &[&cited_by[..], &Blake2_128Concat::hash(&vec![3u8].encode())].concat(),
+            &(),
+        );
+        unhashed::put(
+            &legacy_key(
+                PendingRevocations::<Test, DefaultInstance>::final_prefix(),
+                &[3],
+            ),
+            &5u64,
+        );
+        unhashed::put(
+            &[
+                &RevocationQueue::<Test, DefaultInstance>::final_prefix()[..],
+                &Twox64Concat::hash(&5u64.encode()),
+            ]
+            .concat(),
+            &vec![vec![3u8]],
+        );
+
+        migration::migrate::<Test, DefaultInstance>();
+
+        assert_eq!(
+            PoeModule::version_history(0, vec![1]),
+            vec![vec![1], vec![2]]
+        );
+        assert_eq!(PoeModule::references(0, vec![3]), vec![vec![2]]);
+        assert_eq!(PoeModule::cited_by(0, vec![2]), vec![vec![3]]);
+        assert_eq!(PoeModule::proofs(0, vec![3]).unwrap().owner, 1);
+        assert_eq!(PoeModule::pending_revocation(0, vec![3]), Some(5));
+
+        run_to_block(5);
+        assert_eq!(PoeModule::proofs(0, vec![3]), None);
+    });
+}
+
+#[test]
+fn migrate_to_v5_indexes_claims_by_block() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<DefaultInstance>::put(Releases::V4);
+        Proofs::<Test, DefaultInstance>::insert(
+            0,
+            vec![1],
+            Claim {
+                owner: 1,
+                block_number: 3,
+                deposit: 10,
+                metadata: ClaimMetadata::default(),
+                metadata_deposit: 0,
+                algorithm: None,
+            },
+        );
+
+        migration::migrate::<Test, DefaultInstance>();
+
+        assert_eq!(StorageVersion::<DefaultInstance>::get(), Releases::V5);
+        assert_eq!(PoeModule::claims_by_block(3), vec![(0, vec![1])]);
+    });
+}
+
+#[test]
+fn migrate_to_v5_recounts_claims() {
+    new_test_ext().execute_with(|| {
+        // Only the claim created after the counters were introduced was counted.
+        StorageVersion::<DefaultInstance>::put(Releases::V4);
+        for (proof, owner) in &[(1, 1), (2, 1), (3, 2)] {
+            Proofs::<Test, DefaultInstance>::insert(
+                0,
+                vec![*proof],
+                Claim {
+                    owner: *owner,
+                    block_number: 1,
+                    deposit: 10,
+                    metadata: ClaimMetadata::default(),
+                    metadata_deposit: 0,
+                    algorithm: None,
+                },
+            );
+        }
+        ClaimCount::<DefaultInstance>::put(1);
+        ClaimsCreatedTotal::<DefaultInstance>::put(1);
+        OwnerClaimCount::<Test, DefaultInstance>::insert(2, 1);
+
+        migration::migrate::<Test, DefaultInstance>();
+
+        assert_eq!(
+            PoeModule::stats(),
+            ClaimStats {
+                count: 3,
+                created: 3,
+                revoked: 0,
+                expired: 0,
+            }
+        );
+        assert_eq!(PoeModule::owner_claim_count(1), 2);
+        assert_eq!(PoeModule::owner_claim_count(2), 1);
+    });
+}
+
+#[test]
+fn migrate_from_v2_records_no_algorithm() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<DefaultInstance>::put(Releases::V2);
+        let proofs = Proofs::<Test, DefaultInstance>::final_prefix();
+        unhashed::put(&legacy_key(proofs, &[1]), &legacy_claim(1));
+        unhashed::put(&legacy_key(proofs, &[2]), &first_release_claim(2));
+        // Neither layout, so removed.
+        unhashed::put(&legacy_key(proofs, &[3]), &(2u64, 1u64, 0u8));
+
+        migration::migrate::<Test, DefaultInstance>();
+
+        assert_eq!(StorageVersion::<DefaultInstance>::get(), Releases::V5);
+        assert_eq!(
+            PoeModule::proofs(0, vec![1]),
+            Some(Claim {
+                owner: 1,
+                block_number: 1,
+                deposit: 10,
+                metadata: ClaimMetadata::default(),
+                metadata_deposit: 0,
+                algorithm: None,
+            })
+        );
+        assert_eq!(PoeModule::proofs(0, vec![2]).map(|c| c.deposit), Some(0));
+        assert_eq!(PoeModule::proofs(0, vec![3]), None);
+        assert_eq!(PoeModule::stats().count, 2);
+    });
+}
+
+#[test]
+fn migrate_converts_claims_of_the_first_release() {
+    new_test_ext().execute_with(|| {
+        // The first release stored `(owner, block_number)` and reserved nothing.
+        unhashed::put(
+            &legacy_key(Proofs::<Test, DefaultInstance>::final_prefix(), &[1]),
+            &(1u64, 3u64),
+        );
+
+        migration::migrate::<Test, DefaultInstance>();
+
+        assert_eq!(
+            PoeModule::proofs(0, vec![1]),
+            Some(Claim {
+                owner: 1,
+                block_number: 3,
+                deposit: 0,
+                metadata: ClaimMetadata::default(),
+                metadata_deposit: 0,
+                algorithm: None,
+            })
+        );
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), 0, vec![1], None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn create_claim_from_content_hashes_with_algorithm() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(HashPoe::create_claim_from_content(
+            Origin::signed(1),
+            0,
+            HashAlgorithm::Sha2_256,
+            b"hello".to_vec(),
+            None
+        ));
+
+        let digest = sp_io::hashing::sha2_256(b"hello");
+        let claim = HashPoe::proofs(0, H256(digest)).unwrap();
+        assert_eq!(claim.owner, 1);
+        assert_eq!(claim.algorithm, Some(HashAlgorithm::Sha2_256));
+        assert_eq!(
+            HashPoe::claim_of_content(0, HashAlgorithm::Sha2_256, b"hello"),
+            Some(claim)
+        );
+        assert_eq!(
+            HashPoe::claim_of_content(0, HashAlgorithm::Keccak256, b"hello"),
+            None
+        );
+        // The same digest is not taken for a Keccak-256 digest.
+        assert_eq!(
+            HashPoe::claim_of_digest(0, HashAlgorithm::Keccak256, &digest),
+            None
+        );
+
+        assert_noop!(
+            HashPoe::create_claim_from_content(
+                Origin::signed(1),
+                0,
+                HashAlgorithm::Keccak256,
+                vec![0; 65],
+                None
+            ),
+            Error::<Test, Instance2>::ContentTooLong
+        );
+    });
+}
+
+#[test]
+fn create_claim_with_content_keeps_only_the_digest_in_state() {
+    new_test_ext().execute_with(|| {
+        let content = vec![7; 100];
+        assert_ok!(HashPoe::create_claim_with_content(
+            Origin::signed(1),
+            0,
+            HashAlgorithm::Blake2_256,
+            content.clone(),
+            None
+        ));
+
+        let proof = H256(sp_io::hashing::blake2_256(&content));
+        assert_eq!(
+            HashPoe::proofs(0, proof).unwrap().algorithm,
+            Some(HashAlgorithm::Blake2_256)
+        );
+        assert_eq!(HashPoe::document(0, proof), None);
+        assert_ne!(
+            HashPoe::offchain_document_key(0, &proof),
+            HashPoe::offchain_document_key(1, &proof)
+        );
+        assert_ne!(
+            PoeModule::offchain_document_key(0, &vec![1]),
+            OtherPoe::offchain_document_key(0, &vec![1])
+        );
+
+        assert_noop!(
+            HashPoe::create_claim_with_content(
+                Origin::signed(1),
+                0,
+                HashAlgorithm::Blake2_256,
+                vec![0; 129],
+                None
+            ),
+            Error::<Test, Instance2>::DocumentTooLong
+        );
+    });
+}
+
+#[test]
+fn content_calls_are_weighed_by_length() {
+    let weight = |len: usize| {
+        crate::Call::<Test>::create_claim_with_content(
+            0,
+            HashAlgorithm::Blake2_256,
+            vec![0; len],
+            None,
+        )
+        .get_dispatch_info()
+        .weight
+    };
+    assert_eq!(weight(1_000) - weight(0), 1_000 * WEIGHT_PER_CONTENT_BYTE);
+}
+
+#[test]
+fn create_claim_with_algorithm_records_it() {
+    new_test_ext().execute_with(|| {
+        let digest = [1; 32];
+        assert_ok!(HashPoe::create_claim_with_algorithm(
+            Origin::signed(1),
+            0,
+            H256(digest),
+            HashAlgorithm::Keccak256,
+            None
+        ));
+        assert_ok!(HashPoe::create_claim(
+            Origin::signed(1),
+            0,
+            H256([2; 32]),
+            None
+        ));
+
+        assert!(HashPoe::claim_of_digest(0, HashAlgorithm::Keccak256, &digest).is_some());
+        assert!(HashPoe::claim_of_digest(0, HashAlgorithm::Blake2_256, &digest).is_none());
+        // Claims recording no algorithm match any.
+        assert!(HashPoe::claim_of_digest(0, HashAlgorithm::Blake2_256, &[2; 32]).is_some());
+    });
+}
+
+#[test]
+fn byte_proofs_of_digests_are_multihashes() {
+    for algorithm in &[
+        HashAlgorithm::Blake2_256,
+        HashAlgorithm::Sha2_256,
+        HashAlgorithm::Keccak256,
+    ] {
+        let proof = Vec::<u8>::from_digest(*algorithm, &[1; 32]).unwrap();
+        assert!(Multihash::is_valid(&proof));
+        assert!(proof.ends_with(&[1; 32]));
+        assert_eq!(proof.algorithm(), Some(*algorithm));
+    }
+    assert_eq!(vec![0x12u8, 0x20, 1].algorithm(), None);
+    assert_eq!(H256::repeat_byte(1).algorithm(), None);
+    assert_eq!(
+        Vec::<u8>::from_digest(HashAlgorithm::Sha2_256, &[1; 32]).unwrap()[..2],
+        [0x12, 0x20]
+    );
+}
+
+#[test]
+fn instances_are_independent() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 2, 2, 3],
+            None
+        ));
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(2),
+            0,
+            vec![0, 2, 2, 3],
+            None
+        ));
+
+        assert_eq!(PoeModule::proofs(0, vec![0, 2, 2, 3]).unwrap().owner, 1);
+        assert_eq!(OtherPoe::proofs(0, vec![0, 2, 2, 3]).unwrap().owner, 2);
+        assert_eq!(Balances::reserved_balance(1), 10);
+        assert_eq!(Balances::reserved_balance(2), 50);
+
+        assert_noop!(
+            OtherPoe::create_claim(Origin::signed(1), 0, vec![0, 1], None),
+            Error::<Test, Instance1>::ProofTooShort
+        );
+    });
+}
+
+#[test]
+fn create_claim_checks_proof_format() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            OtherPoe::create_claim(Origin::signed(1), 0, vec![0, 3, 2, 3], None),
+            Error::<Test, Instance1>::InvalidProofFormat
+        );
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0x12, 2, 2, 3],
+            None
+        ));
+    });
+}
+
+#[test]
+fn exact_32_bytes_validator() {
+    assert!(Exact32Bytes::is_valid(&[0; 32]));
+    assert!(!Exact32Bytes::is_valid(&[0; 31]));
+    assert!(!Exact32Bytes::is_valid(&[0; 33]));
+}
+
+#[test]
+fn multihash_validator() {
+    let mut sha2_256 = vec![0x12, 0x20];
+    sha2_256.extend_from_slice(&[1; 32]);
+    assert!(Multihash::is_valid(&sha2_256));
+
+    // Blake2b-256 has a two byte varint code.
+    let mut blake2b_256 = vec![0xa0, 0xe4, 0x02, 0x20];
+    blake2b_256.extend_from_slice(&[1; 32]);
+    assert!(Multihash::is_valid(&blake2b_256));
+
+    // Digest shorter or longer than announced.
+    assert!(!Multihash::is_valid(&sha2_256[..33]));
+    sha2_256.push(0);
+    assert!(!Multihash::is_valid(&sha2_256));
+
+    // Empty digest, truncated and non-minimal varints.
+    assert!(!Multihash::is_valid(&[0x12, 0x00]));
+    assert!(!Multihash::is_valid(&[0x80]));
+    assert!(!Multihash::is_valid(&[0x92, 0x00, 0x01, 0x01]));
+}
+
+#[test]
+fn cid_v1_validator() {
+    // CIDv1, raw codec, SHA-256 multihash.
+    let mut cid = vec![0x01, 0x55, 0x12, 0x20];
+    cid.extend_from_slice(&[1; 32]);
+    assert!(CidV1::is_valid(&cid));
+
+    // CIDv0 is a bare multihash and has no version prefix.
+    assert!(!CidV1::is_valid(&cid[2..]));
+
+    cid[0] = 0x02;
+    assert!(!CidV1::is_valid(&cid));
+}
+
+#[test]
+fn amend_claim_links_versions() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            vec![2]
+        ));
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![2],
+            vec![3]
+        ));
+
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 1);
+        assert_eq!(PoeModule::proofs(0, vec![3]).unwrap().owner, 1);
+        assert_eq!(PoeModule::supersedes(0, vec![2]), Some(vec![1]));
+        assert_eq!(PoeModule::superseded_by(0, vec![2]), Some(vec![3]));
+        assert_eq!(Balances::reserved_balance(1), 30);
+
+        let history = vec![vec![1], vec![2], vec![3]];
+        assert_eq!(PoeModule::version_history(0, vec![1]), history);
+        assert_eq!(PoeModule::version_history(0, vec![2]), history);
+        assert_eq!(PoeModule::version_history(0, vec![3]), history);
+        assert!(PoeModule::version_history(0, vec![4]).is_empty());
+    });
+}
+
+#[test]
+fn amend_claim_checks_old_claim() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeModule::amend_claim(Origin::signed(1), 0, vec![1], vec![2]),
+            Error::<Test>::NoSuchProof
+        );
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_noop!(
+            PoeModule::amend_claim(Origin::signed(2), 0, vec![1], vec![2]),
+            Error::<Test>::NotProofOwner
+        );
+
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            vec![2]
+        ));
+        assert_noop!(
+            PoeModule::amend_claim(Origin::signed(1), 0, vec![1], vec![3]),
+            Error::<Test>::ClaimSuperseded
+        );
+    });
+}
+
+#[test]
+fn amend_claim_requires_unclaimed_new_proof() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), 0, vec![2], None));
+
+        assert_noop!(
+            PoeModule::amend_claim(Origin::signed(1), 0, vec![1], vec![2]),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+        assert_eq!(PoeModule::superseded_by(0, vec![1]), None);
+    });
+}
+
+#[test]
+fn revoke_claim_keeps_history_consistent() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            vec![2]
+        ));
+
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None),
+            Error::<Test>::ClaimSuperseded
+        );
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![2], None));
+        assert_eq!(PoeModule::supersedes(0, vec![2]), None);
+        assert_eq!(PoeModule::superseded_by(0, vec![1]), None);
+        assert_eq!(PoeModule::version_history(0, vec![1]), vec![vec![1]]);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn create_claim_with_references_indexes_citations() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), 0, vec![2], None));
+        assert_ok!(PoeModule::create_claim_with_references(
+            Origin::signed(2),
+            0,
+            vec![3],
+            None,
+            vec![vec![1], vec![2]]
+        ));
+        assert_ok!(PoeModule::create_claim_with_references(
+            Origin::signed(1),
+            0,
+            vec![4],
+            None,
+            vec![vec![1]]
+        ));
+
+        assert_eq!(PoeModule::references(0, vec![3]), vec![vec![1], vec![2]]);
+        assert_eq!(PoeModule::references(0, vec![1]), Vec::<Vec<u8>>::new());
+        let mut citing = PoeModule::cited_by(0, vec![1]);
+        citing.sort();
+        assert_eq!(citing, vec![vec![3], vec![4]]);
+        assert_eq!(PoeModule::cited_by(0, vec![2]), vec![vec![3]]);
+    });
+}
+
+#[test]
+fn create_claim_with_references_checks_references() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![2], None));
+
+        assert_noop!(
+            PoeModule::create_claim_with_references(
+                Origin::signed(1),
+                0,
+                vec![9],
+                None,
+                vec![vec![5]]
+            ),
+            Error::<Test>::NoSuchReference
+        );
+        assert_noop!(
+            PoeModule::create_claim_with_references(
+                Origin::signed(1),
+                0,
+                vec![9],
+                None,
+                vec![vec![1], vec![1]]
+            ),
+            Error::<Test>::DuplicateReference
+        );
+        assert_noop!(
+            PoeModule::create_claim_with_references(
+                Origin::signed(1),
+                0,
+                vec![9],
+                None,
+                vec![vec![1], vec![2], vec![3]]
+            ),
+            Error::<Test>::TooManyReferences
+        );
+    });
+}
+
+#[test]
+fn revoke_claim_drops_citations() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim_with_references(
+            Origin::signed(1),
+            0,
+            vec![2],
+            None,
+            vec![vec![1]]
+        ));
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![2], None));
+        assert!(PoeModule::cited_by(0, vec![1]).is_empty());
+        assert!(PoeModule::references(0, vec![2]).is_empty());
+
+        assert_ok!(PoeModule::create_claim_with_references(
+            Origin::signed(1),
+            0,
+            vec![3],
+            None,
+            vec![vec![1]]
+        ));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+        assert!(PoeModule::cited_by(0, vec![1]).is_empty());
+        assert_eq!(PoeModule::references(0, vec![3]), vec![vec![1]]);
+    });
+}
+
+#[test]
+fn request_revoke_without_delay_revokes_at_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+
+        assert_ok!(PoeModule::request_revoke(
+            Origin::signed(1),
+            0,
+            vec![1],
+            None
+        ));
+        assert_eq!(PoeModule::proofs(0, vec![1]), None);
+        assert_eq!(PoeModule::pending_revocation(0, vec![1]), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn revocation_waits_for_delay() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+
+        assert_noop!(
+            OtherPoe::revoke_claim(Origin::signed(1), 0, proof.clone(), None),
+            Error::<Test, Instance1>::RevocationDelayed
+        );
+        assert_noop!(
+            OtherPoe::request_revoke(Origin::signed(2), 0, proof.clone(), None),
+            Error::<Test, Instance1>::NotProofOwner
+        );
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_eq!(OtherPoe::pending_revocation(0, &proof), Some(4));
+        assert_noop!(
+            OtherPoe::request_revoke(Origin::signed(1), 0, proof.clone(), None),
+            Error::<Test, Instance1>::RevocationPending
+        );
+
+        run_to_block(3);
+        assert!(OtherPoe::proofs(0, &proof).is_some());
+        assert_eq!(Balances::reserved_balance(1), 50);
+
+        run_to_block(4);
+        assert_eq!(OtherPoe::proofs(0, &proof), None);
+        assert_eq!(OtherPoe::pending_revocation(0, &proof), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn cancel_revoke_keeps_claim() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_noop!(
+            OtherPoe::cancel_revoke(Origin::signed(1), 0, proof.clone()),
+            Error::<Test, Instance1>::NoPendingRevocation
+        );
+
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        run_to_block(2);
+        assert_noop!(
+            OtherPoe::cancel_revoke(Origin::signed(2), 0, proof.clone()),
+            Error::<Test, Instance1>::NotProofOwner
+        );
+        assert_ok!(OtherPoe::cancel_revoke(Origin::signed(1), 0, proof.clone()));
+        assert_eq!(OtherPoe::pending_revocation(0, &proof), None);
+
+        // A new request is not affected by the cancelled one.
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        run_to_block(4);
+        assert!(OtherPoe::proofs(0, &proof).is_some());
+        run_to_block(5);
+        assert_eq!(OtherPoe::proofs(0, &proof), None);
+    });
+}
+
+#[test]
+fn claim_pending_revocation_cannot_be_amended() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+
+        assert_noop!(
+            OtherPoe::amend_claim(Origin::signed(1), 0, proof.clone(), vec![0, 2, 2, 4]),
+            Error::<Test, Instance1>::RevocationPending
+        );
+
+        run_to_block(4);
+        assert_eq!(OtherPoe::proofs(0, &proof), None);
+    });
+}
+
+#[test]
+fn claims_expire() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0x12, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_eq!(OtherPoe::expiry(0, &proof), Some(21));
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+        assert_eq!(PoeModule::expiry(0, vec![0, 1]), None);
+
+        run_to_block(20);
+        assert!(OtherPoe::proofs(0, &proof).is_some());
+
+        run_to_block(21);
+        assert_eq!(OtherPoe::proofs(0, &proof), None);
+        assert_eq!(OtherPoe::expiry(0, &proof), None);
+        assert_eq!(OtherPoe::tombstone(0, &proof), None);
+        assert_eq!(Balances::reserved_balance(1), 1);
+        assert!(PoeModule::proofs(0, vec![0, 1]).is_some());
+        assert_eq!(
+            OtherPoe::stats(),
+            ClaimStats {
+                count: 0,
+                created: 1,
+                revoked: 0,
+                expired: 1,
+            }
+        );
+
+        // Expired proofs are free to be claimed again, whatever the reclaim policy.
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(2),
+            0,
+            proof.clone(),
+            None
+        ));
+    });
+}
+
+#[test]
+fn renew_claim_extends_expiry() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0x12, 2, 2, 3];
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        let issuance = Balances::total_issuance();
+
+        assert_ok!(OtherPoe::renew_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            10
+        ));
+        assert_eq!(OtherPoe::expiry(0, &proof), Some(31));
+        assert_eq!(Balances::free_balance(1), 40);
+        assert_eq!(Balances::total_issuance(), issuance - 10);
+
+        run_to_block(21);
+        assert!(OtherPoe::proofs(0, &proof).is_some());
+
+        run_to_block(31);
+        assert_eq!(OtherPoe::proofs(0, &proof), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn renew_claim_requires_live_expiring_claim() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0x12, 2, 2, 3];
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), 10),
+            Error::<Test, Instance1>::NoSuchProof
+        );
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 1],
+            None
+        ));
+        assert_noop!(
+            PoeModule::renew_claim(Origin::signed(1), 0, vec![0, 1], 10),
+            Error::<Test, DefaultInstance>::ClaimDoesNotExpire
+        );
+
+        assert_ok!(OtherPoe::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(2), 0, proof.clone(), 10),
+            Error::<Test, Instance1>::NotProofOwner
+        );
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), 0),
+            Error::<Test, Instance1>::ZeroRenewal
+        );
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), u64::max_value()),
+            Error::<Test, Instance1>::RenewalTooLong
+        );
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), 1_000),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+
+        System::set_block_number(21);
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), 10),
+            Error::<Test, Instance1>::ClaimExpired
+        );
+
+        System::set_block_number(20);
+        assert_ok!(OtherPoe::request_revoke(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        assert_noop!(
+            OtherPoe::renew_claim(Origin::signed(1), 0, proof.clone(), 10),
+            Error::<Test, Instance1>::RevocationPending
+        );
+    });
+}
+
+#[test]
+fn accepted_offer_moves_claim_and_deposits() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            Some(metadata())
+        ));
+        let reserved = Balances::reserved_balance(1);
+
+        assert_noop!(
+            PoeModule::offer_claim(Origin::signed(2), 0, vec![1], 2),
+            Error::<Test>::NotProofOwner
+        );
+        assert_noop!(
+            PoeModule::offer_claim(Origin::signed(1), 0, vec![2], 2),
+            Error::<Test>::NoSuchProof
+        );
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+        assert_eq!(
+            PoeModule::pending_transfers(0, vec![1]),
+            Some(PendingTransfer { to: 2, expires: 11 })
+        );
+
+        // Nothing changes hands until the recipient accepts.
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 1);
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(3), 0, vec![1]),
+            Error::<Test>::NotOfferRecipient
+        );
+
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), 0, vec![1]));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(PoeModule::pending_transfers(0, vec![1]), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), reserved);
+
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None),
+            Error::<Test>::NotProofOwner
+        );
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), 0, vec![1], None));
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn accept_claim_requires_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 3));
+
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(3), 0, vec![1]),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn offers_expire() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+
+        System::set_block_number(12);
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(2), 0, vec![1]),
+            Error::<Test>::OfferExpired
+        );
+
+        // Offering again restarts the period.
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), 0, vec![1]));
+    });
+}
+
+#[test]
+fn cancel_offer_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_noop!(
+            PoeModule::cancel_offer(Origin::signed(1), 0, vec![1]),
+            Error::<Test>::NoSuchOffer
+        );
+
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+        assert_noop!(
+            PoeModule::cancel_offer(Origin::signed(3), 0, vec![1]),
+            Error::<Test>::NotProofOwner
+        );
+        assert_ok!(PoeModule::cancel_offer(Origin::signed(1), 0, vec![1]));
+        assert_noop!(
+            PoeModule::accept_claim(Origin::signed(2), 0, vec![1]),
+            Error::<Test>::NoSuchOffer
+        );
+
+        // The recipient may decline.
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+        assert_ok!(PoeModule::cancel_offer(Origin::signed(2), 0, vec![1]));
+        assert_eq!(PoeModule::pending_transfers(0, vec![1]), None);
+
+        // Revoking a claim drops its offer.
+        assert_ok!(PoeModule::offer_claim(Origin::signed(1), 0, vec![1], 2));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![1], None));
+        assert_eq!(PoeModule::pending_transfers(0, vec![1]), None);
+    });
+}
+
+#[test]
+fn buy_claim_pays_owner_and_moves_claim() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_noop!(
+            PoeModule::buy_claim(Origin::signed(2), 0, vec![1], 30),
+            Error::<Test>::NotForSale
+        );
+        assert_noop!(
+            PoeModule::set_price(Origin::signed(2), 0, vec![1], Some(20)),
+            Error::<Test>::NotProofOwner
+        );
+
+        assert_ok!(PoeModule::set_price(
+            Origin::signed(1),
+            0,
+            vec![1],
+            Some(20)
+        ));
+        assert_eq!(PoeModule::price(0, vec![1]), Some(20));
+        assert_noop!(
+            PoeModule::buy_claim(Origin::signed(1), 0, vec![1], 20),
+            Error::<Test>::AlreadyOwner
+        );
+        assert_noop!(
+            PoeModule::buy_claim(Origin::signed(2), 0, vec![1], 19),
+            Error::<Test>::PriceTooHigh
+        );
+
+        assert_ok!(PoeModule::buy_claim(Origin::signed(2), 0, vec![1], 30));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(PoeModule::price(0, vec![1]), None);
+        assert_eq!(Balances::free_balance(1), 120);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 70);
+        assert_eq!(Balances::reserved_balance(2), 10);
     });
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn buy_claim_is_atomic() {
     new_test_ext().execute_with(|| {
-        // Ensure the expected error is thrown when no value is present.
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        // The buyer can pay the price but not the deposit.
+        assert_ok!(PoeModule::set_price(Origin::signed(1), 0, vec![1], Some(1)));
+
+        assert_noop!(
+            PoeModule::buy_claim(Origin::signed(3), 0, vec![1], 1),
+            pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+        );
+        assert_eq!(Balances::free_balance(1), 90);
+    });
+}
+
+#[test]
+fn set_price_withdraws_listing() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::set_price(
+            Origin::signed(1),
+            0,
+            vec![1],
+            Some(20)
+        ));
+        assert_ok!(PoeModule::set_price(Origin::signed(1), 0, vec![1], None));
+
+        assert_noop!(
+            PoeModule::buy_claim(Origin::signed(2), 0, vec![1], 30),
+            Error::<Test>::NotForSale
+        );
+    });
+}
+
+#[test]
+fn approved_account_can_transfer_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_noop!(
+            PoeModule::transfer_from(Origin::signed(2), 0, vec![1], 2),
+            Error::<Test>::NotApproved
+        );
+        assert_noop!(
+            PoeModule::approve(Origin::signed(2), 0, vec![1], Some(2)),
+            Error::<Test>::NotApproved
+        );
+
+        assert_ok!(PoeModule::approve(Origin::signed(1), 0, vec![1], Some(2)));
+        assert_eq!(PoeModule::approved(0, vec![1]), Some(2));
+
+        assert_ok!(PoeModule::transfer_from(Origin::signed(2), 0, vec![1], 2));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 10);
+        // The approval does not survive the transfer.
+        assert_eq!(PoeModule::approved(0, vec![1]), None);
+    });
+}
+
+#[test]
+fn transfer_from_offers_claim_to_other_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        // Owners go through offers, so that deposits are never pushed onto another account.
+        assert_noop!(
+            PoeModule::transfer_from(Origin::signed(1), 0, vec![1], 2),
+            Error::<Test>::NotApproved
+        );
+
+        assert_ok!(PoeModule::approve(Origin::signed(1), 0, vec![1], Some(3)));
+        assert_ok!(PoeModule::transfer_from(Origin::signed(3), 0, vec![1], 2));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 1);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(PoeModule::pending_transfers(0, vec![1]).unwrap().to, 2);
+
+        assert_ok!(PoeModule::accept_claim(Origin::signed(2), 0, vec![1]));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 10);
+    });
+}
+
+#[test]
+fn approve_can_be_cleared() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::approve(Origin::signed(1), 0, vec![1], Some(2)));
+        assert_ok!(PoeModule::approve(Origin::signed(1), 0, vec![1], None));
+
+        assert_noop!(
+            PoeModule::transfer_from(Origin::signed(2), 0, vec![1], 2),
+            Error::<Test>::NotApproved
+        );
+    });
+}
+
+#[test]
+fn operator_manages_all_claims() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 0, vec![2], None));
+        assert_ok!(PoeModule::set_approval_for_all(Origin::signed(1), 2, true));
+        assert!(PoeModule::is_approved_for_all(1, 2));
+
+        assert_ok!(PoeModule::approve(Origin::signed(2), 0, vec![1], Some(3)));
+        assert_eq!(PoeModule::approved(0, vec![1]), Some(3));
+        assert_ok!(PoeModule::transfer_from(Origin::signed(2), 0, vec![2], 2));
+        assert_eq!(PoeModule::proofs(0, vec![2]).unwrap().owner, 2);
+
+        assert_ok!(PoeModule::set_approval_for_all(Origin::signed(1), 2, false));
+        assert!(!PoeModule::is_approved_for_all(1, 2));
+        assert_noop!(
+            PoeModule::transfer_from(Origin::signed(2), 0, vec![1], 2),
+            Error::<Test>::NotApproved
+        );
+    });
+}
+
+#[test]
+fn create_org_makes_creator_admin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_org(Origin::signed(1)));
+        assert_ok!(PoeModule::create_org(Origin::signed(2)));
+
+        assert_eq!(PoeModule::next_org_id(), 2);
+        assert_eq!(PoeModule::org_member(0, 1), Some(OrgRole::Admin));
+        assert_eq!(PoeModule::org_member(1, 2), Some(OrgRole::Admin));
+        assert_eq!(PoeModule::org_admins(0), Some(1));
+        assert_ne!(PoeModule::org_account_id(0), PoeModule::org_account_id(1));
+        assert_eq!(
+            PoeModule::org_of_account(PoeModule::org_account_id(1)),
+            Some(1)
+        );
+    });
+}
+
+#[test]
+fn org_members_are_managed_by_admins() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_org(Origin::signed(1)));
+
+        assert_noop!(
+            PoeModule::add_org_member(Origin::signed(2), 0, 3, OrgRole::Claimer),
+            Error::<Test>::InsufficientOrgRole
+        );
+        assert_noop!(
+            PoeModule::add_org_member(Origin::signed(1), 1, 3, OrgRole::Claimer),
+            Error::<Test>::NoSuchOrg
+        );
+        assert_ok!(PoeModule::add_org_member(
+            Origin::signed(1),
+            0,
+            2,
+            OrgRole::Claimer
+        ));
+        assert_noop!(
+            PoeModule::add_org_member(Origin::signed(2), 0, 3, OrgRole::Claimer),
+            Error::<Test>::InsufficientOrgRole
+        );
+        assert_noop!(
+            PoeModule::remove_org_member(Origin::signed(2), 0, 1),
+            Error::<Test>::InsufficientOrgRole
+        );
+        assert_noop!(
+            PoeModule::remove_org_member(Origin::signed(1), 0, 3),
+            Error::<Test>::NotOrgMember
+        );
+
+        // Members may leave on their own.
+        assert_ok!(PoeModule::remove_org_member(Origin::signed(2), 0, 2));
+        assert_eq!(PoeModule::org_member(0, 2), None);
+    });
+}
+
+#[test]
+fn org_keeps_an_admin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_org(Origin::signed(1)));
+
+        assert_noop!(
+            PoeModule::remove_org_member(Origin::signed(1), 0, 1),
+            Error::<Test>::LastOrgAdmin
+        );
+        assert_noop!(
+            PoeModule::add_org_member(Origin::signed(1), 0, 1, OrgRole::Claimer),
+            Error::<Test>::LastOrgAdmin
+        );
+
+        assert_ok!(PoeModule::add_org_member(
+            Origin::signed(1),
+            0,
+            2,
+            OrgRole::Admin
+        ));
+        assert_eq!(PoeModule::org_admins(0), Some(2));
+        assert_ok!(PoeModule::add_org_member(
+            Origin::signed(2),
+            0,
+            1,
+            OrgRole::Claimer
+        ));
+        assert_eq!(PoeModule::org_admins(0), Some(1));
+        assert_noop!(
+            PoeModule::remove_org_member(Origin::signed(2), 0, 2),
+            Error::<Test>::LastOrgAdmin
+        );
+    });
+}
+
+#[test]
+fn org_claims_are_owned_by_org_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_org(Origin::signed(1)));
+        assert_ok!(PoeModule::add_org_member(
+            Origin::signed(1),
+            0,
+            2,
+            OrgRole::Claimer
+        ));
+        let org_account = PoeModule::org_account_id(0);
+        Balances::make_free_balance_be(&org_account, 100);
+
+        assert_noop!(
+            PoeModule::create_claim_as_org(Origin::signed(3), 0, 0, vec![1], None),
+            Error::<Test>::NotOrgMember
+        );
+        assert_noop!(
+            PoeModule::create_claim_as_org(Origin::signed(2), 1, 0, vec![1], None),
+            Error::<Test>::NoSuchOrg
+        );
+        assert_ok!(PoeModule::create_claim_as_org(
+            Origin::signed(2),
+            0,
+            0,
+            vec![1],
+            None
+        ));
+
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, org_account);
+        assert_eq!(Balances::reserved_balance(&org_account), 10);
+        assert_eq!(Balances::reserved_balance(2), 0);
+
+        // Only admins manage the claims of the organization.
+        assert_noop!(
+            PoeModule::revoke_claim(Origin::signed(2), 0, vec![1], None),
+            Error::<Test>::NotProofOwner
+        );
+        assert_ok!(PoeModule::set_metadata(
+            Origin::signed(1),
+            0,
+            vec![1],
+            metadata()
+        ));
+        assert_eq!(
+            Balances::reserved_balance(&org_account),
+            10 + metadata().encoded_size() as u64
+        );
+        assert_eq!(Balances::reserved_balance(1), 0);
+
+        assert_ok!(PoeModule::amend_claim(
+            Origin::signed(1),
+            0,
+            vec![1],
+            vec![2]
+        ));
+        assert_eq!(PoeModule::proofs(0, vec![2]).unwrap().owner, org_account);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 0, vec![2], None));
+        assert_eq!(PoeModule::proofs(0, vec![2]), None);
+    });
+}
+
+#[test]
+fn claims_are_scoped_to_namespaces() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+        assert_eq!(PoeModule::namespace(1), Some((1, 20)));
+        assert_eq!(Balances::reserved_balance(1), 20);
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), 0, vec![1], None));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 1, vec![1], None));
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(PoeModule::proofs(1, vec![1]).unwrap().owner, 1);
+
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 1, vec![1], None),
+            Error::<Test>::ProofAlreadyClaimed
+        );
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), 1, vec![2], None),
+            Error::<Test>::NotNamespaceOwner
+        );
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 2, vec![2], None),
+            Error::<Test>::NoSuchNamespace
+        );
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), 1, vec![1], None));
+        assert_eq!(PoeModule::proofs(1, vec![1]), None);
+        assert!(PoeModule::tombstone(1, vec![1]).is_some());
+        assert_eq!(PoeModule::proofs(0, vec![1]).unwrap().owner, 2);
+        assert_eq!(PoeModule::tombstone(0, vec![1]), None);
+    });
+}
+
+#[test]
+fn identifiers_do_not_wrap_around() {
+    new_test_ext().execute_with(|| {
+        NextOrgId::<DefaultInstance>::put(u32::max_value());
+        assert_noop!(
+            PoeModule::create_org(Origin::signed(1)),
+            Error::<Test>::OrgIdOverflow
+        );
+
+        NextNamespaceId::<DefaultInstance>::put(u32::max_value());
+        assert_noop!(
+            PoeModule::register_namespace(Origin::signed(1)),
+            Error::<Test>::NamespaceIdOverflow
+        );
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn accepted_namespace_offer_moves_ownership_and_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+        assert_ok!(PoeModule::create_org(Origin::signed(2)));
+        assert_ok!(PoeModule::add_org_member(
+            Origin::signed(2),
+            0,
+            3,
+            OrgRole::Claimer
+        ));
+        let org_account = PoeModule::org_account_id(0);
+        Balances::make_free_balance_be(&org_account, 100);
+
+        assert_noop!(
+            PoeModule::offer_namespace(Origin::signed(2), 1, org_account),
+            Error::<Test>::NotNamespaceOwner
+        );
+        assert_ok!(PoeModule::offer_namespace(
+            Origin::signed(1),
+            1,
+            org_account
+        ));
+        // Nothing is reserved from the recipient until it accepts.
+        assert_eq!(PoeModule::namespace(1), Some((1, 20)));
+        assert_eq!(Balances::reserved_balance(&org_account), 0);
+        assert_noop!(
+            PoeModule::accept_namespace(Origin::signed(3), 1),
+            Error::<Test>::NotOfferRecipient
+        );
+
+        assert_ok!(PoeModule::accept_namespace(Origin::signed(2), 1));
+        assert_eq!(PoeModule::namespace(1), Some((org_account, 20)));
+        assert_eq!(PoeModule::pending_namespace_transfer(1), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(&org_account), 20);
+
+        // Members of the organization claim in its namespace on its behalf.
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 1, vec![1], None),
+            Error::<Test>::NotNamespaceOwner
+        );
+        assert_ok!(PoeModule::create_claim_as_org(
+            Origin::signed(3),
+            0,
+            1,
+            vec![1],
+            None
+        ));
+        assert_eq!(PoeModule::proofs(1, vec![1]).unwrap().owner, org_account);
+    });
+}
+
+#[test]
+fn namespace_offers_expire() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+        assert_noop!(
+            PoeModule::accept_namespace(Origin::signed(2), 1),
+            Error::<Test>::NoSuchOffer
+        );
+        assert_ok!(PoeModule::offer_namespace(Origin::signed(1), 1, 2));
+
+        System::set_block_number(12);
+        assert_noop!(
+            PoeModule::accept_namespace(Origin::signed(2), 1),
+            Error::<Test>::OfferExpired
+        );
+        assert_eq!(PoeModule::namespace(1), Some((1, 20)));
+    });
+}
+
+#[test]
+fn deregistered_namespace_releases_deposit_and_keeps_claims() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), 1, vec![1], None));
+        assert_ok!(PoeModule::offer_namespace(Origin::signed(1), 1, 2));
+
+        assert_noop!(
+            PoeModule::deregister_namespace(Origin::signed(2), 1),
+            Error::<Test>::NotNamespaceOwner
+        );
+        assert_ok!(PoeModule::deregister_namespace(Origin::signed(1), 1));
+        assert_eq!(PoeModule::namespace(1), None);
+        assert_eq!(PoeModule::pending_namespace_transfer(1), None);
+        assert_eq!(Balances::reserved_balance(1), 10);
+
+        assert_eq!(PoeModule::proofs(1, vec![1]).unwrap().owner, 1);
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), 1, vec![2], None),
+            Error::<Test>::NoSuchNamespace
+        );
+        assert_ok!(PoeModule::register_namespace(Origin::signed(1)));
+        assert_eq!(PoeModule::namespace(2), Some((1, 20)));
+    });
+}
+
+#[test]
+fn offchain_worker_reports_verification() {
+    let (offchain, state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![3]);
+    state.write().expect_request(PendingRequest {
+        method: "GET".into(),
+        uri: "http://localhost/doc".into(),
+        response: Some(b"hello".to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+
+    ext.execute_with(|| {
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            offchain::GATEWAY_KEY,
+            b"http://localhost/",
+        );
+        let proof = BlakeTwo256::hash(b"hello");
+        let metadata = ClaimMetadata {
+            uri: Some(b"doc".to_vec()),
+            ..Default::default()
+        };
+        assert_ok!(HashPoe::create_claim_with_algorithm(
+            Origin::signed(1),
+            0,
+            proof,
+            HashAlgorithm::Blake2_256,
+            Some(metadata)
+        ));
+        assert_eq!(HashPoe::unverified_claims(), vec![(0, proof)]);
+
+        HashPoe::offchain_worker(1);
+        let tx = pool_state.write().transactions.pop().unwrap();
+        let tx = Extrinsic::<crate::Call<Test, Instance2>>::decode(&mut &*tx).unwrap();
+        assert_eq!(tx.signature, Some((0, ())));
+        assert_eq!(tx.call, crate::Call::report_verification(0, proof, true));
+
+        assert_ok!(HashPoe::set_verifiers(Origin::root(), vec![3]));
+        assert_noop!(
+            HashPoe::report_verification(Origin::signed(3), 0, H256::zero(), true),
+            Error::<Test, Instance2>::NoSuchProof
+        );
+        assert_ok!(HashPoe::report_verification(
+            Origin::signed(3),
+            0,
+            proof,
+            true
+        ));
+        assert_eq!(HashPoe::verification(0, &proof, &3), Some(true));
+
+        run_to_block(2);
+        assert!(HashPoe::unverified_claims().is_empty());
+        assert_ok!(HashPoe::revoke_claim(Origin::signed(1), 0, proof, None));
+        assert_eq!(HashPoe::verification(0, &proof, &3), None);
+        assert!(HashPoe::verifications(0, proof).is_empty());
+    });
+}
+
+#[test]
+fn offchain_worker_skips_claims_of_unknown_algorithm() {
+    let (offchain, _state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![3]);
+
+    ext.execute_with(|| {
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            offchain::GATEWAY_KEY,
+            b"http://localhost/",
+        );
+        // A raw digest does not tell which hash function produced it.
+        let metadata = ClaimMetadata {
+            uri: Some(b"doc".to_vec()),
+            ..Default::default()
+        };
+        assert_ok!(HashPoe::create_claim(
+            Origin::signed(1),
+            0,
+            BlakeTwo256::hash(b"hello"),
+            Some(metadata)
+        ));
+
+        HashPoe::offchain_worker(1);
+        assert!(pool_state.read().transactions.is_empty());
+    });
+}
+
+#[test]
+fn offchain_worker_only_fetches_paths_relative_to_gateway() {
+    let (offchain, _state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![3]);
+
+    ext.execute_with(|| {
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            offchain::GATEWAY_KEY,
+            b"http://localhost/",
+        );
+        let uris: [&[u8]; 6] = [
+            b"http://169.254.169.254/doc",
+            b"//evil.example/doc",
+            b"/etc/passwd",
+            b"../admin",
+            b"user@evil.example/doc",
+            b"%2e%2e/admin",
+        ];
+        for (block, uri) in uris.iter().enumerate() {
+            let metadata = ClaimMetadata {
+                uri: Some(uri.to_vec()),
+                ..Default::default()
+            };
+            assert_ok!(HashPoe::create_claim_with_algorithm(
+                Origin::signed(1),
+                0,
+                BlakeTwo256::hash(uri),
+                HashAlgorithm::Blake2_256,
+                Some(metadata)
+            ));
+
+            HashPoe::offchain_worker(block as u64 + 1);
+            assert!(pool_state.read().transactions.is_empty());
+            run_to_block(block as u64 + 2);
+        }
+    });
+}
+
+#[test]
+fn offchain_worker_ignores_documents_longer_than_max_content_length() {
+    let (offchain, state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![3]);
+    let document = vec![7; MaxContentLength::get() as usize + 1];
+    state.write().expect_request(PendingRequest {
+        method: "GET".into(),
+        uri: "http://localhost/doc".into(),
+        response: Some(document.clone()),
+        sent: true,
+        ..Default::default()
+    });
+
+    ext.execute_with(|| {
+        // The gateway is joined to the URI with a slash.
+        sp_io::offchain::local_storage_set(
+            StorageKind::PERSISTENT,
+            offchain::GATEWAY_KEY,
+            b"http://localhost",
+        );
+        let metadata = ClaimMetadata {
+            uri: Some(b"doc".to_vec()),
+            ..Default::default()
+        };
+        assert_ok!(HashPoe::create_claim_with_algorithm(
+            Origin::signed(1),
+            0,
+            BlakeTwo256::hash(&document),
+            HashAlgorithm::Blake2_256,
+            Some(metadata)
+        ));
+
+        HashPoe::offchain_worker(1);
+        assert!(pool_state.read().transactions.is_empty());
+    });
+}
+
+#[test]
+fn only_verifiers_report_verifications() {
+    new_test_ext().execute_with(|| {
+        let proof = H256::repeat_byte(1);
+        assert_ok!(HashPoe::create_claim(Origin::signed(1), 0, proof, None));
+        assert_noop!(
+            HashPoe::report_verification(Origin::signed(2), 0, proof, true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_noop!(
+            HashPoe::set_verifiers(Origin::signed(1), vec![2]),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            HashPoe::set_verifiers(Origin::root(), vec![2, 3, 4]),
+            Error::<Test, Instance2>::TooManyVerifiers
+        );
+        assert_ok!(HashPoe::set_verifiers(Origin::root(), vec![3, 2, 3]));
+        assert_eq!(HashPoe::verifiers(), vec![2, 3]);
+
+        assert_ok!(HashPoe::report_verification(
+            Origin::signed(2),
+            0,
+            proof,
+            true
+        ));
+        assert_noop!(
+            HashPoe::report_verification(Origin::signed(4), 0, proof, true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(HashPoe::set_paused(Origin::root(), true));
+        assert_noop!(
+            HashPoe::report_verification(Origin::signed(3), 0, proof, true),
+            Error::<Test, Instance2>::PalletPaused
+        );
+    });
+}
+
+#[test]
+fn verifications_are_bounded_per_claim() {
+    new_test_ext().execute_with(|| {
+        let proof = H256::repeat_byte(1);
+        assert_ok!(HashPoe::create_claim(Origin::signed(1), 0, proof, None));
+        assert_ok!(HashPoe::set_verifiers(Origin::root(), vec![2, 3]));
+
+        assert_ok!(HashPoe::report_verification(
+            Origin::signed(2),
+            0,
+            proof,
+            true
+        ));
+        assert_ok!(HashPoe::report_verification(
+            Origin::signed(3),
+            0,
+            proof,
+            false
+        ));
+
+        // Reports of former verifiers are kept, so new ones may find the claim full.
+        assert_ok!(HashPoe::set_verifiers(Origin::root(), vec![3, 4]));
+        assert_noop!(
+            HashPoe::report_verification(Origin::signed(4), 0, proof, true),
+            Error::<Test, Instance2>::TooManyVerifications
+        );
+
+        // Verifiers already recorded can still change their report.
+        assert_ok!(HashPoe::report_verification(
+            Origin::signed(3),
+            0,
+            proof,
+            true
+        ));
+        assert_eq!(HashPoe::verifications(0, proof), vec![(2, true), (3, true)]);
+    });
+}
+
+#[test]
+fn claim_events_are_indexed_by_proof_and_accounts() {
+    new_test_ext().execute_with(|| {
+        let proof = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            None
+        ));
+        let proof_topic = PoeModule::proof_topic(0, &proof);
+        assert_ne!(proof_topic, PoeModule::proof_topic(1, &proof));
+        assert_ne!(proof_topic, OtherPoe::proof_topic(0, &proof));
+        assert_eq!(
+            System::events().pop().unwrap().topics,
+            vec![proof_topic, PoeModule::account_topic(&1)]
+        );
+
+        assert_ok!(PoeModule::offer_claim(
+            Origin::signed(1),
+            0,
+            proof.clone(),
+            2
+        ));
+        assert_eq!(
+            System::events().pop().unwrap().topics,
+            vec![
+                proof_topic,
+                PoeModule::account_topic(&1),
+                PoeModule::account_topic(&2)
+            ]
+        );
+
+        assert_ok!(PoeModule::set_paused(Origin::root(), true));
+        assert!(System::events().pop().unwrap().topics.is_empty());
+    });
+}
+
+#[test]
+fn org_claim_events_name_the_org_as_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_org(Origin::signed(2)));
+        let org_account = PoeModule::org_account_id(0);
+        Balances::make_free_balance_be(&org_account, 100);
+        assert_ok!(PoeModule::create_claim_as_org(
+            Origin::signed(2),
+            0,
+            0,
+            vec![1],
+            None
+        ));
+        let owner_topics = vec![
+            PoeModule::proof_topic(0, &[1].to_vec()),
+            PoeModule::account_topic(&org_account),
+        ];
+
+        assert_ok!(PoeModule::set_metadata(
+            Origin::signed(2),
+            0,
+            vec![1],
+            ClaimMetadata::default()
+        ));
+        assert_eq!(System::events().pop().unwrap().topics, owner_topics);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), 0, vec![1], None));
+        assert_eq!(System::events().pop().unwrap().topics, owner_topics);
+    });
+}
+
+#[test]
+fn claims_in_range_pages_through_blocks() {
+    new_test_ext().execute_with(|| {
+        for proof in &[vec![0, 1], vec![0, 2]] {
+            assert_ok!(PoeModule::create_claim(
+                Origin::signed(1),
+                0,
+                proof.clone(),
+                None
+            ));
+        }
+        run_to_block(3);
+        assert_ok!(PoeModule::create_claim(
+            Origin::signed(1),
+            0,
+            vec![0, 3],
+            None
+        ));
+
+        let page = PoeModule::claims_in_range(1, 5, None, 2);
+        assert_eq!(
+            page,
+            ClaimsPage {
+                claims: vec![(1, 0, vec![0, 1]), (1, 0, vec![0, 2])],
+                next: Some((3, 0)),
+            }
+        );
+        assert_eq!(
+            PoeModule::claims_in_range(1, 5, page.next, 2),
+            ClaimsPage {
+                claims: vec![(3, 0, vec![0, 3])],
+                next: None,
+            }
+        );
+        assert_eq!(
+            PoeModule::claims_in_range(1, 1, Some((1, 1)), 2).claims,
+            vec![(1, 0, vec![0, 2])]
+        );
+        // A limit of zero still moves the cursor forward.
+        assert_eq!(
+            PoeModule::claims_in_range(1, 5, None, 0),
+            ClaimsPage {
+                claims: vec![(1, 0, vec![0, 1])],
+                next: Some((1, 1)),
+            }
+        );
+
+        for proof in &[vec![0, 1], vec![0, 2]] {
+            assert_ok!(PoeModule::revoke_claim(
+                Origin::signed(1),
+                0,
+                proof.clone(),
+                None
+            ));
+        }
+        assert!(!ClaimsByBlock::<Test, DefaultInstance>::contains_key(1));
+        assert_eq!(PoeModule::claims_in_range(1, 2, None, 2).claims, vec![]);
+    });
+}
+
+#[test]
+fn claims_per_block_are_bounded() {
+    new_test_ext().execute_with(|| {
+        for byte in 1..=2 {
+            assert_ok!(HashPoe::create_claim(
+                Origin::signed(1),
+                0,
+                H256::repeat_byte(byte),
+                None
+            ));
+        }
         assert_noop!(
-            TemplateModule::cause_error(Origin::signed(1)),
-            Error::<Test>::NoneValue
+            HashPoe::create_claim(Origin::signed(1), 0, H256::repeat_byte(3), None),
+            Error::<Test, Instance2>::TooManyClaimsInBlock
         );
+
+        run_to_block(2);
+        assert_ok!(HashPoe::create_claim(
+            Origin::signed(1),
+            0,
+            H256::repeat_byte(3),
+            None
+        ));
     });
 }
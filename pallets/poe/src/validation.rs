@@ -0,0 +1,80 @@
+//! Checks on the format of proofs, applied before a proof can be claimed.
+
+/// Decides whether a proof is well formed.
+pub trait ProofValidator {
+    /// Whether `proof` has the format expected by the registry.
+    fn is_valid(proof: &[u8]) -> bool;
+}
+
+/// Accepts any proof.
+impl ProofValidator for () {
+    fn is_valid(_proof: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Accepts raw 32 byte digests, such as a bare Blake2-256 or SHA-256 hash.
+pub struct Exact32Bytes;
+
+impl ProofValidator for Exact32Bytes {
+    fn is_valid(proof: &[u8]) -> bool {
+        proof.len() == 32
+    }
+}
+
+/// Accepts a [multihash](https://multiformats.io/multihash/): the varint code of the hash
+/// function, the varint length of the digest and the digest itself.
+pub struct Multihash;
+
+impl ProofValidator for Multihash {
+    fn is_valid(proof: &[u8]) -> bool {
+        let mut input = proof;
+        read_multihash(&mut input).is_some() && input.is_empty()
+    }
+}
+
+/// Accepts the binary form of an IPFS [CIDv1](https://github.com/multiformats/cid): the varint
+/// version `1`, the varint multicodec of the content and the multihash of the content.
+pub struct CidV1;
+
+impl ProofValidator for CidV1 {
+    fn is_valid(proof: &[u8]) -> bool {
+        let mut input = proof;
+        read_varint(&mut input) == Some(1)
+            && read_varint(&mut input).is_some()
+            && read_multihash(&mut input).is_some()
+            && input.is_empty()
+    }
+}
+
+/// The longest varint allowed by the multiformats specification, in bytes.
+const MAX_VARINT_LENGTH: usize = 9;
+
+/// Read an unsigned LEB128 varint from the front of `input`.
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+    let bytes: &[u8] = *input;
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(MAX_VARINT_LENGTH) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            // Reject non-minimal encodings, which would let one digest take many forms.
+            if i > 0 && byte == 0 {
+                return None;
+            }
+            *input = &bytes[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Read a multihash from the front of `input`, returning its hash function code.
+fn read_multihash(input: &mut &[u8]) -> Option<u64> {
+    let code = read_varint(input)?;
+    let length = read_varint(input)?;
+    if length == 0 || length > input.len() as u64 {
+        return None;
+    }
+    *input = &input[length as usize..];
+    Some(code)
+}
@@ -4,7 +4,7 @@
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// https://substrate.dev/docs/en/knowledgebase/runtime/frame
 
-use frame_support::{decl_module, decl_storage, decl_event, decl_error, dispatch, traits::Get};
+use frame_support::{decl_module, decl_storage, decl_event, decl_error, dispatch, traits::Get, StorageMap};
 use frame_system::ensure_signed;
 
 #[cfg(test)]
@@ -29,6 +29,8 @@ decl_storage! {
 		// Learn more about declaring storage items:
 		// https://substrate.dev/docs/en/knowledgebase/runtime/storage#declaring-storage-items
 		Something get(fn something): Option<u32>;
+		/// The number of live claims held by each account.
+		Reputation get(fn reputation): map hasher(blake2_128_concat) T::AccountId => u32;
 	}
 }
 
@@ -101,3 +103,21 @@ decl_module! {
 		}
 	}
 }
+
+impl<T: Trait> Module<T> {
+	/// Credit `who` for a notarized claim.
+	pub fn note_claim_created(who: &T::AccountId) {
+		Reputation::<T>::mutate(who, |reputation| *reputation = reputation.saturating_add(1));
+	}
+
+	/// Move the credit for a claim from its previous owner `from` to its new owner `to`.
+	pub fn note_claim_transferred(from: &T::AccountId, to: &T::AccountId) {
+		Self::note_claim_revoked(from);
+		Self::note_claim_created(to);
+	}
+
+	/// Take back the credit of `who` for a claim that has been revoked.
+	pub fn note_claim_revoked(who: &T::AccountId) {
+		Reputation::<T>::mutate(who, |reputation| *reputation = reputation.saturating_sub(1));
+	}
+}
@@ -21,3 +21,22 @@ fn correct_error_for_none_value() {
 		);
 	});
 }
+
+#[test]
+fn reputation_follows_claims() {
+	new_test_ext().execute_with(|| {
+		TemplateModule::note_claim_created(&1);
+		TemplateModule::note_claim_created(&1);
+		TemplateModule::note_claim_revoked(&1);
+		assert_eq!(TemplateModule::reputation(1), 1);
+
+		// Never goes below zero.
+		TemplateModule::note_claim_revoked(&2);
+		assert_eq!(TemplateModule::reputation(2), 0);
+
+		// The credit follows the claim.
+		TemplateModule::note_claim_transferred(&1, &2);
+		assert_eq!(TemplateModule::reputation(1), 0);
+		assert_eq!(TemplateModule::reputation(2), 1);
+	});
+}
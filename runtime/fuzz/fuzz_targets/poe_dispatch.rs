@@ -0,0 +1,165 @@
+//! Fuzz the runtime dispatch path of the PoE and template pallets.
+//!
+//! The input is read as a sequence of `(signer, Call)` pairs. Every call that targets one of
+//! the proof registries or `TemplateModule` is signed with one of a few well-known keys and
+//! applied through `Executive` inside a block built on top of the runtime genesis. Besides
+//! panics, the target checks that a dispatch which fails leaves the storage of these pallets
+//! untouched.
+#![no_main]
+
+use codec::{Decode, Encode};
+use libfuzzer_sys::fuzz_target;
+use node_template_runtime::{
+    AccountId, BalancesConfig, Call, Executive, GenesisConfig, Header, Index, Runtime, SignedExtra,
+    SudoConfig, SystemConfig, UncheckedExtrinsic, SLOT_DURATION,
+};
+use sp_core::Pair;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+    generic::{Era, SignedPayload},
+    traits::Header as _,
+    BuildStorage,
+};
+
+/// Accounts that sign the fuzzed calls, selected by the leading byte of each call.
+const SIGNERS: [AccountKeyring; 4] = [
+    AccountKeyring::Alice,
+    AccountKeyring::Bob,
+    AccountKeyring::Charlie,
+    AccountKeyring::Dave,
+];
+
+/// Storage prefixes of the pallets whose state is checked after a failed dispatch.
+const CHECKED_PALLETS: [&[u8]; 4] = [
+    b"PoeModule",
+    b"Instance1PoeModule",
+    b"Instance2PoeModule",
+    b"TemplateModule",
+];
+
+fn new_test_ext() -> sp_io::TestExternalities {
+    GenesisConfig {
+        frame_system: Some(SystemConfig {
+            code: vec![],
+            changes_trie_config: Default::default(),
+        }),
+        pallet_balances: Some(BalancesConfig {
+            balances: SIGNERS
+                .iter()
+                .map(|k| (k.to_account_id(), 1 << 60))
+                .collect(),
+        }),
+        pallet_aura: None,
+        pallet_grandpa: None,
+        pallet_sudo: Some(SudoConfig {
+            key: AccountKeyring::Alice.to_account_id(),
+        }),
+        pallet_poe: Some(Default::default()),
+        pallet_poe_Instance1: Some(Default::default()),
+        pallet_poe_Instance2: Some(Default::default()),
+    }
+    .build_storage()
+    .expect("genesis config is valid; qed")
+    .into()
+}
+
+fn signed_extra(nonce: Index) -> SignedExtra {
+    (
+        frame_system::CheckSpecVersion::new(),
+        frame_system::CheckTxVersion::new(),
+        frame_system::CheckGenesis::new(),
+        frame_system::CheckEra::from(Era::Immortal),
+        frame_system::CheckNonce::from(nonce),
+        frame_system::CheckWeight::new(),
+        pallet_transaction_payment::ChargeTransactionPayment::from(0),
+    )
+}
+
+fn sign(signer: AccountKeyring, call: Call) -> UncheckedExtrinsic {
+    let who: AccountId = signer.to_account_id();
+    let nonce = frame_system::Module::<Runtime>::account_nonce(&who);
+    let payload = SignedPayload::new(call, signed_extra(nonce))
+        .expect("signed extensions of the runtime are valid in genesis state; qed");
+    let signature = payload.using_encoded(|p| signer.pair().sign(p));
+    let (call, extra, _) = payload.deconstruct();
+
+    UncheckedExtrinsic::new_signed(call, who, signature.into(), extra)
+}
+
+/// Whether a decoded call belongs to one of the pallets under test.
+fn is_fuzzed(call: &Call) -> bool {
+    match call {
+        Call::PoeModule(_)
+        | Call::ContractPoe(_)
+        | Call::ReleasePoe(_)
+        | Call::TemplateModule(_) => true,
+        _ => false,
+    }
+}
+
+/// Raw key/value pairs stored under the prefix of a pallet.
+fn pallet_state(pallet: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let prefix = sp_io::hashing::twox_128(pallet);
+    let mut state = Vec::new();
+    let mut key = prefix.to_vec();
+
+    while let Some(next) = sp_io::storage::next_key(&key) {
+        if !next.starts_with(&prefix) {
+            break;
+        }
+        let value = sp_io::storage::get(&next).unwrap_or_default();
+        state.push((next.clone(), value));
+        key = next;
+    }
+
+    state
+}
+
+fn checked_state() -> Vec<Vec<(Vec<u8>, Vec<u8>)>> {
+    CHECKED_PALLETS.iter().map(|p| pallet_state(p)).collect()
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut data = data;
+
+    new_test_ext().execute_with(|| {
+        Executive::initialize_block(&Header::new(
+            1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        ));
+        // `pallet_timestamp` insists on being set once per block before it is finalized.
+        let now = Call::Timestamp(pallet_timestamp::Call::set(SLOT_DURATION));
+        Executive::apply_extrinsic(UncheckedExtrinsic::new_unsigned(now))
+            .expect("timestamp inherent is valid; qed")
+            .expect("timestamp inherent dispatches; qed");
+
+        while let Some((&selector, rest)) = data.split_first() {
+            data = rest;
+            let call = match Call::decode(&mut data) {
+                Ok(call) => call,
+                Err(_) => break,
+            };
+            if !is_fuzzed(&call) {
+                continue;
+            }
+
+            let signer = SIGNERS[selector as usize % SIGNERS.len()];
+            let before = checked_state();
+            let dispatched = match Executive::apply_extrinsic(sign(signer, call)) {
+                Ok(outcome) => outcome.is_ok(),
+                Err(_) => false,
+            };
+            if !dispatched {
+                assert!(
+                    before == checked_state(),
+                    "failed dispatch modified pallet storage"
+                );
+            }
+        }
+
+        Executive::finalize_block();
+    });
+});
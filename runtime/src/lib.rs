@@ -6,18 +6,21 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::Encode;
 use pallet_grandpa::fg_primitives;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
+use pallet_poe_runtime_api::Registry;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::traits::{
-    BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor, Saturating, Verify,
+    self, BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor,
+    SaturatedConversion, Saturating, Verify,
 };
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
     transaction_validity::{TransactionSource, TransactionValidity},
-    ApplyExtrinsicResult, MultiSignature,
+    ApplyExtrinsicResult, ModuleId, MultiSignature,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -27,7 +30,7 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
     construct_runtime, parameter_types,
-    traits::{KeyOwnerProofSystem, Randomness},
+    traits::{Filter, KeyOwnerProofSystem, Randomness},
     weights::{
         constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
         IdentityFee, Weight,
@@ -98,10 +101,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("node-template"),
     impl_name: create_runtime_str!("node-template"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 2,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -136,9 +139,23 @@ parameter_types! {
 
 // Configure FRAME pallets to include in runtime.
 
+/// Rejects calls of paused proof registries before they are dispatched.
+pub struct BaseFilter;
+
+impl Filter<Call> for BaseFilter {
+    fn filter(call: &Call) -> bool {
+        match call {
+            Call::PoeModule(call) => PoeModule::is_call_allowed(call),
+            Call::ContractPoe(call) => ContractPoe::is_call_allowed(call),
+            Call::ReleasePoe(call) => ReleasePoe::is_call_allowed(call),
+            _ => true,
+        }
+    }
+}
+
 impl frame_system::Trait for Runtime {
     /// The basic call filter to use in dispatchable.
-    type BaseCallFilter = ();
+    type BaseCallFilter = BaseFilter;
     /// The identifier used to distinguish between accounts.
     type AccountId = AccountId;
     /// The aggregated dispatch type that is available for extrinsics.
@@ -269,8 +286,222 @@ impl pallet_template::Trait for Runtime {
     type Event = Event;
 }
 
+/// Credits the owners of claims with reputation in the template pallet.
+pub struct ClaimReputation;
+
+impl pallet_poe::OnClaimCreated<AccountId> for ClaimReputation {
+    fn on_claim_created(owner: &AccountId, _proof: &[u8]) {
+        TemplateModule::note_claim_created(owner);
+    }
+}
+
+impl pallet_poe::OnClaimTransferred<AccountId> for ClaimReputation {
+    fn on_claim_transferred(from: &AccountId, to: &AccountId, _proof: &[u8]) {
+        TemplateModule::note_claim_transferred(from, to);
+    }
+}
+
+impl pallet_poe::OnClaimRevoked<AccountId> for ClaimReputation {
+    fn on_claim_revoked(owner: &AccountId, _proof: &[u8]) {
+        TemplateModule::note_claim_revoked(owner);
+    }
+}
+
+parameter_types! {
+    pub const ClaimDeposit: Balance = 1_000;
+    pub const MinProofLength: u32 = 1;
+    pub const MaxProofLength: u32 = 256;
+    pub const ProofHashing: pallet_poe::HashAlgorithm = pallet_poe::HashAlgorithm::Blake2_256;
+    pub const MaxContentLength: u32 = 4 * 1024;
+    pub const MaxMetadataLength: u32 = 256;
+    pub const MetadataDepositPerByte: Balance = 10;
+    pub const MaxReferences: u32 = 16;
+    pub const MaxClaimsPerBlock: u32 = 1_000;
+    pub const MaxInlineDocument: u32 = 1024;
+    pub const DocumentDepositPerByte: Balance = 10;
+    pub const MaxIndexedDocument: u32 = 64 * 1024;
+    pub const NamespaceDeposit: Balance = 10_000;
+    pub const PoeModuleId: ModuleId = ModuleId(*b"py/poe00");
+    pub const OfferExpiry: BlockNumber = 7 * DAYS;
+    pub const RevokeDelay: BlockNumber = 0;
+    pub const Reclaim: pallet_poe::ReclaimPolicy = pallet_poe::ReclaimPolicy::OwnerOnly;
+    pub const NoReclaim: pallet_poe::ReclaimPolicy = pallet_poe::ReclaimPolicy::Deny;
+    pub const ClaimPeriod: BlockNumber = 0;
+    pub const RenewalFeePerBlock: Balance = 1;
+    pub const MaxVerifications: u32 = 16;
+}
+
+/// The general purpose proof registry.
 impl pallet_poe::Trait for Runtime {
     type Event = Event;
+    type Currency = Balances;
+    type Proof = Vec<u8>;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = ClaimDeposit;
+    type MinProofLength = MinProofLength;
+    type MaxProofLength = MaxProofLength;
+    type ProofValidator = pallet_poe::validation::Multihash;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = MaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = PoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = RevokeDelay;
+    type ReclaimPolicy = Reclaim;
+    type ClaimPeriod = ClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = ClaimReputation;
+    type OnClaimTransferred = ClaimReputation;
+    type OnClaimRevoked = ClaimReputation;
+    type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+    type VerifierOrigin = frame_system::EnsureSignedBy<PoeModule, AccountId>;
+    type VerifierManagerOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = pallet_poe::offchain::crypto::VerifierId;
+}
+
+parameter_types! {
+    pub const ContractClaimDeposit: Balance = 10_000;
+    pub const ContractMinProofLength: u32 = 32;
+    pub const ContractMaxProofLength: u32 = 64;
+    pub const ContractPoeModuleId: ModuleId = ModuleId(*b"py/poect");
+    pub const ContractRevokeDelay: BlockNumber = 2 * DAYS;
+    pub const ContractClaimPeriod: BlockNumber = 365 * DAYS;
+}
+
+/// Proofs of signed contracts.
+impl pallet_poe::Trait<pallet_poe::Instance1> for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type Proof = Vec<u8>;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = ContractClaimDeposit;
+    type MinProofLength = ContractMinProofLength;
+    type MaxProofLength = ContractMaxProofLength;
+    type ProofValidator = pallet_poe::validation::Multihash;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = MaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = ContractPoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = ContractRevokeDelay;
+    type ReclaimPolicy = NoReclaim;
+    type ClaimPeriod = ContractClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = ClaimReputation;
+    type OnClaimTransferred = ClaimReputation;
+    type OnClaimRevoked = ClaimReputation;
+    type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+    type VerifierOrigin = frame_system::EnsureSignedBy<ContractPoe, AccountId>;
+    type VerifierManagerOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = pallet_poe::offchain::crypto::VerifierId;
+}
+
+parameter_types! {
+    pub const ReleaseClaimDeposit: Balance = 500;
+    pub const ReleaseProofLength: u32 = 32;
+    pub const ReleasePoeModuleId: ModuleId = ModuleId(*b"py/poerl");
+}
+
+/// Proofs of published software releases, identified by their 32 byte digest.
+impl pallet_poe::Trait<pallet_poe::Instance2> for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type Proof = Hash;
+    type ProofHashing = ProofHashing;
+    type MaxContentLength = MaxContentLength;
+    type ClaimDeposit = ReleaseClaimDeposit;
+    type MinProofLength = ReleaseProofLength;
+    type MaxProofLength = ReleaseProofLength;
+    type ProofValidator = pallet_poe::validation::Exact32Bytes;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type MaxReferences = MaxReferences;
+    type MaxClaimsPerBlock = MaxClaimsPerBlock;
+    type MaxInlineDocument = MaxInlineDocument;
+    type DocumentDepositPerByte = DocumentDepositPerByte;
+    type MaxIndexedDocument = MaxIndexedDocument;
+    type NamespaceDeposit = NamespaceDeposit;
+    type ModuleId = ReleasePoeModuleId;
+    type OfferExpiry = OfferExpiry;
+    type RevokeDelay = RevokeDelay;
+    type ReclaimPolicy = NoReclaim;
+    type ClaimPeriod = ClaimPeriod;
+    type RenewalFeePerBlock = RenewalFeePerBlock;
+    type OnRenewalFee = ();
+    type OnClaimCreated = ClaimReputation;
+    type OnClaimTransferred = ClaimReputation;
+    type OnClaimRevoked = ClaimReputation;
+    type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+    type VerifierOrigin = frame_system::EnsureSignedBy<ReleasePoe, AccountId>;
+    type VerifierManagerOrigin = frame_system::EnsureRoot<AccountId>;
+    type MaxVerifications = MaxVerifications;
+    type AuthorityId = pallet_poe::offchain::crypto::VerifierId;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+    Call: From<LocalCall>,
+{
+    fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: Call,
+        public: <Signature as traits::Verify>::Signer,
+        account: AccountId,
+        nonce: Index,
+    ) -> Option<(
+        Call,
+        <UncheckedExtrinsic as traits::Extrinsic>::SignaturePayload,
+    )> {
+        // The transaction is valid for at most half of the hashes kept by the chain.
+        let period = BlockHashCount::get()
+            .checked_next_power_of_two()
+            .map(|c| c / 2)
+            .unwrap_or(2) as u64;
+        let current_block = System::block_number()
+            .saturated_into::<u64>()
+            .saturating_sub(1);
+        let extra: SignedExtra = (
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+        );
+        let raw_payload = SignedPayload::new(call, extra).ok()?;
+        let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+        let (call, extra, _) = raw_payload.deconstruct();
+        Some((call, (account, signature, extra)))
+    }
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as traits::Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+    Call: From<C>,
+{
+    type OverarchingCall = Call;
+    type Extrinsic = UncheckedExtrinsic;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -290,7 +521,9 @@ construct_runtime!(
         Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
         // Include the custom logic from the template pallet in the runtime.
         TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
-        PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
+        PoeModule: pallet_poe::{Module, Call, Storage, Event<T>, Config},
+        ContractPoe: pallet_poe::<Instance1>::{Module, Call, Storage, Event<T>, Config},
+        ReleasePoe: pallet_poe::<Instance2>::{Module, Call, Storage, Event<T>, Config},
     }
 );
 
@@ -316,6 +549,8 @@ pub type SignedExtra = (
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
@@ -327,6 +562,66 @@ pub type Executive = frame_executive::Executive<
     AllModules,
 >;
 
+/// Evaluate `$body` against the proof registry `$registry` selects, with `$module` naming its
+/// pallet.
+macro_rules! with_registry {
+    ($registry:expr, |$module:ident| $body:expr) => {
+        match $registry {
+            Registry::General => {
+                type $module = PoeModule;
+                $body
+            }
+            Registry::Contract => {
+                type $module = ContractPoe;
+                $body
+            }
+            Registry::Release => {
+                type $module = ReleasePoe;
+                $body
+            }
+        }
+    };
+}
+
+/// The type of the proofs a registry stores.
+trait RegistryProof {
+    type Proof: pallet_poe::ProofType;
+}
+
+impl<I: pallet_poe::Instance> RegistryProof for pallet_poe::Module<Runtime, I>
+where
+    Runtime: pallet_poe::Trait<I>,
+{
+    type Proof = <Runtime as pallet_poe::Trait<I>>::Proof;
+}
+
+/// The proof of registry `R` made of `bytes`, if they have a form it stores proofs in.
+fn poe_proof<R: RegistryProof>(bytes: &[u8]) -> Option<R::Proof> {
+    pallet_poe::ProofType::from_bytes(bytes)
+}
+
+/// The bytes of `proofs`, as the runtime API returns them.
+fn poe_bytes<P: AsRef<[u8]>>(proofs: Vec<P>) -> Vec<Vec<u8>> {
+    proofs
+        .into_iter()
+        .map(|proof| proof.as_ref().to_vec())
+        .collect()
+}
+
+/// `page` with the bytes of its proofs, as the runtime API returns it.
+fn poe_page<P: AsRef<[u8]>>(
+    page: pallet_poe::ClaimsPage<BlockNumber, P>,
+) -> pallet_poe::ClaimsPage<BlockNumber, Vec<u8>> {
+    pallet_poe::ClaimsPage {
+        claims: page
+            .claims
+            .into_iter()
+            .map(|(block, namespace, proof)| (block, namespace, proof.as_ref().to_vec()))
+            .collect(),
+        next: page.next,
+    }
+}
+
 impl_runtime_apis! {
     impl sp_api::Core<Block> for Runtime {
         fn version() -> RuntimeVersion {
@@ -442,6 +737,137 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber, Balance> for Runtime {
+        fn claim(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<pallet_poe::Claim<AccountId, BlockNumber, Balance>> {
+            with_registry!(registry, |Poe| Poe::proofs(namespace, poe_proof::<Poe>(&proof)?))
+        }
+
+        fn claim_of_digest(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            algorithm: pallet_poe::HashAlgorithm,
+            digest: [u8; 32],
+        ) -> Option<pallet_poe::Claim<AccountId, BlockNumber, Balance>> {
+            with_registry!(registry, |Poe| Poe::claim_of_digest(namespace, algorithm, &digest))
+        }
+
+        fn version_history(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Vec<Vec<u8>> {
+            with_registry!(registry, |Poe| poe_proof::<Poe>(&proof)
+                .map_or_else(Vec::new, |proof| poe_bytes(Poe::version_history(namespace, proof))))
+        }
+
+        fn references(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Vec<Vec<u8>> {
+            with_registry!(registry, |Poe| poe_proof::<Poe>(&proof)
+                .map_or_else(Vec::new, |proof| poe_bytes(Poe::references(namespace, proof))))
+        }
+
+        fn cited_by(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Vec<Vec<u8>> {
+            with_registry!(registry, |Poe| poe_proof::<Poe>(&proof)
+                .map_or_else(Vec::new, |proof| poe_bytes(Poe::cited_by(namespace, proof))))
+        }
+
+        fn document(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<Vec<u8>> {
+            with_registry!(registry, |Poe| Poe::document(namespace, poe_proof::<Poe>(&proof)?))
+        }
+
+        fn offchain_document_key(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<Vec<u8>> {
+            with_registry!(registry, |Poe| Some(Poe::offchain_document_key(
+                namespace,
+                &poe_proof::<Poe>(&proof)?,
+            )))
+        }
+
+        fn approved(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<AccountId> {
+            with_registry!(registry, |Poe| Poe::approved(namespace, poe_proof::<Poe>(&proof)?))
+        }
+
+        fn is_approved_for_all(registry: Registry, owner: AccountId, operator: AccountId) -> bool {
+            with_registry!(registry, |Poe| Poe::is_approved_for_all(owner, operator))
+        }
+
+        fn pending_revocation(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<BlockNumber> {
+            with_registry!(registry, |Poe| Poe::pending_revocation(
+                namespace,
+                poe_proof::<Poe>(&proof)?,
+            ))
+        }
+
+        fn expiry(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<BlockNumber> {
+            with_registry!(registry, |Poe| Poe::expiry(namespace, poe_proof::<Poe>(&proof)?))
+        }
+
+        fn tombstone(
+            registry: Registry,
+            namespace: pallet_poe::NamespaceId,
+            proof: Vec<u8>,
+        ) -> Option<pallet_poe::Tombstone<AccountId, BlockNumber>> {
+            with_registry!(registry, |Poe| Poe::tombstone(namespace, poe_proof::<Poe>(&proof)?))
+        }
+
+        fn stats(registry: Registry) -> pallet_poe::ClaimStats {
+            with_registry!(registry, |Poe| Poe::stats())
+        }
+
+        fn owner_claim_count(registry: Registry, owner: AccountId) -> u32 {
+            with_registry!(registry, |Poe| Poe::owner_claim_count(owner))
+        }
+
+        fn events_by_topic(topic: [u8; 32]) -> Vec<Vec<u8>> {
+            let topic = Hash::from(topic);
+            System::events()
+                .into_iter()
+                .filter(|record| record.topics.contains(&topic))
+                .map(|record| record.encode())
+                .collect()
+        }
+
+        fn claims_in_range(
+            registry: Registry,
+            from: BlockNumber,
+            to: BlockNumber,
+            cursor: Option<(BlockNumber, u32)>,
+            limit: u32,
+        ) -> pallet_poe::ClaimsPage<BlockNumber, Vec<u8>> {
+            with_registry!(registry, |Poe| poe_page(Poe::claims_in_range(from, to, cursor, limit)))
+        }
+    }
+
     impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
         fn query_info(
             uxt: <Block as BlockT>::Extrinsic,
@@ -0,0 +1,532 @@
+//! Integration tests that drive the runtime through `Executive` with signed extrinsics.
+
+use codec::Encode;
+use frame_support::{
+    traits::{Get, OnInitialize},
+    weights::GetDispatchInfo,
+};
+use node_template_runtime::{
+    AccountId, Balance, Balances, BalancesConfig, Block, BlockNumber, Call, ClaimDeposit,
+    ContractPoe, Executive, GenesisConfig, Hash, Header, Index, PoeModule, ReleasePoe, Runtime,
+    SignedExtra, SudoConfig, System, SystemConfig, TemplateModule, TransactionPayment,
+    UncheckedExtrinsic, DAYS, SLOT_DURATION,
+};
+use pallet_poe_runtime_api::{runtime_decl_for_PoeApi::PoeApi as PoeRuntimeApi, Registry};
+use sp_core::Pair;
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+    generic::{Era, SignedPayload},
+    traits::{BlakeTwo256, Hash as _, Header as _},
+    transaction_validity::{InvalidTransaction, TransactionValidityError},
+    BuildStorage, DispatchError,
+};
+
+const INITIAL_BALANCE: Balance = 1 << 60;
+
+fn new_test_ext() -> sp_io::TestExternalities {
+    GenesisConfig {
+        frame_system: Some(SystemConfig {
+            code: vec![],
+            changes_trie_config: Default::default(),
+        }),
+        pallet_balances: Some(BalancesConfig {
+            balances: vec![
+                (AccountKeyring::Alice.to_account_id(), INITIAL_BALANCE),
+                (AccountKeyring::Bob.to_account_id(), INITIAL_BALANCE),
+            ],
+        }),
+        pallet_aura: None,
+        pallet_grandpa: None,
+        pallet_sudo: Some(SudoConfig {
+            key: AccountKeyring::Alice.to_account_id(),
+        }),
+        pallet_poe: Some(Default::default()),
+        pallet_poe_Instance1: Some(Default::default()),
+        pallet_poe_Instance2: Some(Default::default()),
+    }
+    .build_storage()
+    .unwrap()
+    .into()
+}
+
+fn signed_extra(nonce: Index) -> SignedExtra {
+    (
+        frame_system::CheckSpecVersion::new(),
+        frame_system::CheckTxVersion::new(),
+        frame_system::CheckGenesis::new(),
+        frame_system::CheckEra::from(Era::Immortal),
+        frame_system::CheckNonce::from(nonce),
+        frame_system::CheckWeight::new(),
+        pallet_transaction_payment::ChargeTransactionPayment::from(0),
+    )
+}
+
+/// Sign `call` by `signer` with an explicit nonce.
+fn sign_with_nonce(signer: AccountKeyring, call: Call, nonce: Index) -> UncheckedExtrinsic {
+    let payload = SignedPayload::new(call, signed_extra(nonce)).unwrap();
+    let signature = payload.using_encoded(|p| signer.pair().sign(p));
+    let (call, extra, _) = payload.deconstruct();
+
+    UncheckedExtrinsic::new_signed(call, signer.to_account_id(), signature.into(), extra)
+}
+
+/// Sign `call` by `signer` with the next nonce of its account.
+fn sign(signer: AccountKeyring, call: Call) -> UncheckedExtrinsic {
+    let nonce = System::account_nonce(signer.to_account_id());
+    sign_with_nonce(signer, call, nonce)
+}
+
+/// The fee `ChargeTransactionPayment` withdraws for `xt`.
+fn fee_of(xt: &UncheckedExtrinsic) -> Balance {
+    let len = xt.encode().len() as u32;
+    TransactionPayment::compute_fee(len, &xt.get_dispatch_info(), 0)
+}
+
+/// Initialize block `number` and apply its timestamp inherent.
+fn start_block(number: BlockNumber, parent_hash: Hash) {
+    Executive::initialize_block(&Header::new(
+        number,
+        Default::default(),
+        Default::default(),
+        parent_hash,
+        Default::default(),
+    ));
+
+    let now = Call::Timestamp(pallet_timestamp::Call::set(number as u64 * SLOT_DURATION));
+    assert_eq!(
+        Executive::apply_extrinsic(UncheckedExtrinsic::new_unsigned(now)),
+        Ok(Ok(()))
+    );
+}
+
+/// The SHA-256 multihash of `content`, as accepted by the general purpose registry.
+fn proof_of(content: &[u8]) -> Vec<u8> {
+    let mut proof = vec![0x12, 0x20];
+    proof.extend_from_slice(&sp_io::hashing::sha2_256(content));
+    proof
+}
+
+/// The BLAKE2 multihash of `content`, as derived by `store_document`.
+fn blake2_multihash_of(content: &[u8]) -> Vec<u8> {
+    let algorithm = pallet_poe::HashAlgorithm::Blake2_256;
+    <Vec<u8> as pallet_poe::ProofType>::from_digest(algorithm, &algorithm.hash(content)).unwrap()
+}
+
+fn create_claim(proof: &[u8]) -> Call {
+    Call::PoeModule(pallet_poe::Call::create_claim(0, proof.to_vec(), None))
+}
+
+fn revoke_claim(proof: &[u8]) -> Call {
+    Call::PoeModule(pallet_poe::Call::revoke_claim(0, proof.to_vec(), None))
+}
+
+fn free_balance(who: &AccountId) -> Balance {
+    Balances::free_balance(who)
+}
+
+fn claim_owner(proof: &[u8]) -> Option<AccountId> {
+    PoeModule::proofs(0, proof.to_vec()).map(|claim| claim.owner)
+}
+
+#[test]
+fn create_claim_pays_fee_and_stores_claim() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        let fee = fee_of(&xt);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let claim = PoeModule::proofs(0, hello.clone()).unwrap();
+        assert_eq!(claim.owner, alice);
+        assert_eq!(claim.block_number, 1);
+        assert_eq!(
+            free_balance(&alice),
+            INITIAL_BALANCE - fee - ClaimDeposit::get()
+        );
+        assert_eq!(Balances::reserved_balance(&alice), ClaimDeposit::get());
+        assert_eq!(System::account_nonce(&alice), 1);
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn failed_claim_still_pays_fee() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let xt = sign(AccountKeyring::Bob, create_claim(&hello));
+        let fee = fee_of(&xt);
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Ok(Err(pallet_poe::Error::<Runtime>::ProofAlreadyClaimed.into()))
+        );
+
+        assert_eq!(claim_owner(&hello), Some(alice));
+        assert_eq!(free_balance(&bob), INITIAL_BALANCE - fee);
+        assert_eq!(Balances::reserved_balance(&bob), 0);
+        assert_eq!(System::account_nonce(&bob), 1);
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn claim_revoked_in_later_block() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let create = sign(AccountKeyring::Alice, create_claim(&hello));
+        let mut fees = fee_of(&create);
+        assert_eq!(Executive::apply_extrinsic(create), Ok(Ok(())));
+        let header = Executive::finalize_block();
+
+        start_block(2, header.hash());
+        assert_eq!(claim_owner(&hello), Some(alice.clone()));
+
+        let revoke = sign(AccountKeyring::Alice, revoke_claim(&hello));
+        fees += fee_of(&revoke);
+        assert_eq!(Executive::apply_extrinsic(revoke), Ok(Ok(())));
+        Executive::finalize_block();
+
+        assert_eq!(claim_owner(&hello), None);
+        assert_eq!(free_balance(&alice), INITIAL_BALANCE - fees);
+        assert_eq!(Balances::reserved_balance(&alice), 0);
+        assert_eq!(System::account_nonce(&alice), 2);
+    });
+}
+
+#[test]
+fn revoke_by_other_account_is_rejected() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let xt = sign(AccountKeyring::Bob, revoke_claim(&hello));
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Ok(Err(pallet_poe::Error::<Runtime>::NotProofOwner.into()))
+        );
+        assert_eq!(claim_owner(&hello), Some(alice));
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn stale_nonce_is_rejected_without_fee() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        let fee = fee_of(&xt);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let world = proof_of(b"world");
+        let replay = sign_with_nonce(AccountKeyring::Alice, create_claim(&world), 0);
+        assert_eq!(
+            Executive::apply_extrinsic(replay),
+            Err(TransactionValidityError::Invalid(InvalidTransaction::Stale))
+        );
+        assert_eq!(claim_owner(&world), None);
+        assert_eq!(
+            free_balance(&alice),
+            INITIAL_BALANCE - fee - ClaimDeposit::get()
+        );
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn registries_are_independent() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let proof = proof_of(b"release");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&proof));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let call = Call::ContractPoe(pallet_poe::Call::create_claim(0, proof.clone(), None));
+        let xt = sign(AccountKeyring::Bob, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        assert_eq!(claim_owner(&proof), Some(alice));
+        assert_eq!(
+            ContractPoe::proofs(0, proof.clone()).map(|c| c.owner),
+            Some(bob)
+        );
+
+        let call = Call::ContractPoe(pallet_poe::Call::create_claim(0, b"short".to_vec(), None));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Ok(Err(
+                pallet_poe::Error::<Runtime, pallet_poe::Instance1>::ProofTooShort.into()
+            ))
+        );
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn claims_earn_reputation() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let hello = proof_of(b"hello");
+        let world = proof_of(b"world");
+        start_block(1, Default::default());
+
+        for proof in &[&hello, &world] {
+            let xt = sign(AccountKeyring::Alice, create_claim(proof));
+            assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        }
+        assert_eq!(TemplateModule::reputation(&alice), 2);
+
+        let xt = sign(AccountKeyring::Alice, revoke_claim(&hello));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert_eq!(TemplateModule::reputation(&alice), 1);
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn reputation_follows_transferred_claims() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        let offer = pallet_poe::Call::offer_claim(0, hello.clone(), bob.clone());
+        let xt = sign(AccountKeyring::Alice, Call::PoeModule(offer));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        let accept = pallet_poe::Call::accept_claim(0, hello.clone());
+        let xt = sign(AccountKeyring::Bob, Call::PoeModule(accept));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert_eq!(TemplateModule::reputation(&alice), 0);
+        assert_eq!(TemplateModule::reputation(&bob), 1);
+
+        let xt = sign(AccountKeyring::Bob, revoke_claim(&hello));
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert_eq!(TemplateModule::reputation(&bob), 0);
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn paused_registry_calls_are_filtered() {
+    new_test_ext().execute_with(|| {
+        let hello = proof_of(b"hello");
+        start_block(1, Default::default());
+
+        let pause = Call::PoeModule(pallet_poe::Call::set_paused(true));
+        let xt = sign(
+            AccountKeyring::Alice,
+            Call::Sudo(pallet_sudo::Call::sudo(Box::new(pause))),
+        );
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert!(PoeModule::paused());
+
+        let xt = sign(AccountKeyring::Alice, create_claim(&hello));
+        assert_eq!(
+            Executive::apply_extrinsic(xt),
+            Ok(Err(DispatchError::BadOrigin))
+        );
+        assert_eq!(claim_owner(&hello), None);
+
+        // Other registries keep working.
+        let digest = sp_io::hashing::sha2_256(b"hello").into();
+        let call = Call::ReleasePoe(pallet_poe::Call::create_claim(0, digest, None));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn release_digest_is_hashed_on_chain() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let release = b"release tarball".to_vec();
+        start_block(1, Default::default());
+
+        let call = Call::ReleasePoe(pallet_poe::Call::create_claim_from_content(
+            0,
+            pallet_poe::HashAlgorithm::Blake2_256,
+            release.clone(),
+            None,
+        ));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let digest = BlakeTwo256::hash(&release);
+        assert_eq!(ReleasePoe::proofs(0, digest).map(|c| c.owner), Some(alice));
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn sha2_digest_is_claimed_as_multihash() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let contract = b"signed contract".to_vec();
+        start_block(1, Default::default());
+
+        let call = Call::PoeModule(pallet_poe::Call::create_claim_from_content(
+            0,
+            pallet_poe::HashAlgorithm::Sha2_256,
+            contract.clone(),
+            None,
+        ));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let claim = PoeModule::claim_of_content(0, pallet_poe::HashAlgorithm::Sha2_256, &contract);
+        assert_eq!(claim.map(|c| c.owner), Some(alice));
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn document_is_hashed_on_chain_by_general_registry() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let document = b"meeting notes".to_vec();
+        start_block(1, Default::default());
+
+        let call = Call::PoeModule(pallet_poe::Call::create_claim_from_content(
+            0,
+            pallet_poe::HashAlgorithm::Blake2_256,
+            document.clone(),
+            None,
+        ));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let proof = blake2_multihash_of(&document);
+        assert_eq!(claim_owner(&proof), Some(alice));
+        assert_eq!(
+            PoeModule::claim_of_content(0, pallet_poe::HashAlgorithm::Blake2_256, &document)
+                .map(|c| c.algorithm),
+            Some(Some(pallet_poe::HashAlgorithm::Blake2_256))
+        );
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn expired_contracts_can_be_claimed_again() {
+    new_test_ext().execute_with(|| {
+        let contract = proof_of(b"signed contract");
+        start_block(1, Default::default());
+
+        let call = Call::ContractPoe(pallet_poe::Call::create_claim(0, contract.clone(), None));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let expiry = 1 + 365 * DAYS;
+        assert_eq!(ContractPoe::expiry(0, contract.clone()), Some(expiry));
+
+        // Expiry leaves no tombstone behind, so `NoReclaim` does not lock the proof.
+        ContractPoe::on_initialize(expiry);
+        assert_eq!(ContractPoe::proofs(0, contract.clone()), None);
+        assert_eq!(ContractPoe::tombstone(0, contract.clone()), None);
+        assert_eq!(ContractPoe::stats().expired, 1);
+        assert_eq!(ContractPoe::stats().revoked, 0);
+
+        let call = Call::ContractPoe(pallet_poe::Call::create_claim(0, contract.clone(), None));
+        let xt = sign(AccountKeyring::Bob, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+        assert_eq!(
+            ContractPoe::proofs(0, contract).map(|c| c.owner),
+            Some(AccountKeyring::Bob.to_account_id())
+        );
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn general_registry_stores_documents() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let document = b"I agree".to_vec();
+        start_block(1, Default::default());
+
+        let call = Call::PoeModule(pallet_poe::Call::store_document(0, document.clone()));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        // The proof the runtime API serves the document under.
+        let proof = blake2_multihash_of(&document);
+        assert_eq!(claim_owner(&proof), Some(alice));
+        assert_eq!(PoeModule::document(0, proof), Some(document));
+
+        Executive::finalize_block();
+    });
+}
+
+#[test]
+fn runtime_api_serves_every_registry() {
+    new_test_ext().execute_with(|| {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let digest = BlakeTwo256::hash(b"release tarball");
+        start_block(1, Default::default());
+
+        let call = Call::ReleasePoe(pallet_poe::Call::create_claim(0, digest, None));
+        let xt = sign(AccountKeyring::Alice, call);
+        assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+        let claim = |registry, proof: &[u8]| {
+            <Runtime as PoeRuntimeApi<Block, AccountId, BlockNumber, Balance>>::claim(
+                registry,
+                0,
+                proof.to_vec(),
+            )
+        };
+        assert_eq!(
+            claim(Registry::Release, digest.as_bytes()).map(|c| c.owner),
+            Some(alice)
+        );
+        assert_eq!(claim(Registry::General, digest.as_bytes()), None);
+        // Bytes that are not a digest cannot be a proof of the release registry.
+        assert_eq!(claim(Registry::Release, &[1; 31]), None);
+
+        let stats = |registry| {
+            <Runtime as PoeRuntimeApi<Block, AccountId, BlockNumber, Balance>>::stats(registry)
+        };
+        assert_eq!(stats(Registry::Release).count, 1);
+        assert_eq!(stats(Registry::Contract).count, 0);
+
+        Executive::finalize_block();
+    });
+}